@@ -38,7 +38,7 @@ elif os_system == os_list[1]:
         time.sleep(2)
         print("Testing `hello_world.sol`...")
         time.sleep(1)
-        os.system('.\\target\\debug\\sol.exe ./target/debug/examples/hello_world.sol')
+        os.system('.\\target\\debug\\sol.exe run ./target/debug/examples/hello_world.sol')
         time.sleep(2)
     print("This should get you started with Sol!\nYou can change, remove and add features, you get it, and you could even help Sol's repository by making pull requests!\nHappy Coding 😀")
 elif os_system == os_list[2]:
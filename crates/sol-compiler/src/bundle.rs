@@ -0,0 +1,461 @@
+use crate::compiler::Compiler;
+use crate::CompileError;
+use sol_js_builder::{Builder, Import};
+use sol_parser::{Expression, Imports, Node, Program, Statement};
+use std::collections::{HashMap, HashSet};
+
+/// How a bundle is run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// An ES module, which exports what the entry point exports and imports the external modules.
+    Esm,
+    /// A script that runs everything inside of a function, without any imports or exports.
+    Iife,
+}
+
+/// What a module that is imported by a `BundleModule` turned out to be.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Link {
+    /// Another module of the bundle, by its index.
+    Module(usize),
+    /// A module that isn't part of the bundle, like the built-in modules of Sol, which is imported when the
+    /// bundle runs.
+    External,
+}
+
+/// A Sol file that is part of a bundle.
+#[derive(Debug)]
+pub struct BundleModule {
+    /// Where the module came from, which is written above its code.
+    pub name: String,
+    pub program: Program,
+    /// What each module that `program` imports is, by the name that it's imported with. Modules that aren't
+    /// in here are external.
+    pub links: HashMap<String, Link>,
+}
+
+/// An error in one of the modules of a bundle.
+#[derive(Debug)]
+pub struct BundleError {
+    /// The index of the module.
+    pub module: usize,
+    pub err: CompileError,
+}
+
+/// Compile `modules` into a single JavaScript file, with the first module as the entry point. `prelude` is code
+/// that comes first and can be used by every module, like the polyfill.
+///
+/// Every module but the entry point is wrapped in a function that returns its exports, which runs before the
+/// modules that import it. Top-level functions that nothing uses are left out.
+pub fn bundle(modules: Vec<BundleModule>, format: Format, prelude: &str) -> Result<String, BundleError> {
+    let live = live(&modules, format);
+    let order = order(&modules);
+
+    let mut externals: Vec<String> = Vec::new();
+
+    for &i in order.iter() {
+        for (name, _) in uses(&modules[i]).filter(|(_, link)| *link == Link::External) {
+            if ! externals.iter().any(|external| external == name) {
+                externals.push(name.to_owned());
+            }
+        }
+    }
+
+    let mut imports = Builder::new();
+
+    if format == Format::Esm {
+        for (i, external) in externals.iter().enumerate() {
+            let mut import = Import::new(external.clone());
+            import.namespace(format!("__sol_external_{}", i));
+
+            imports.import(import);
+        }
+    }
+
+    let mut parts = vec![imports.source(), prelude.to_owned()];
+    let mut modules = modules.into_iter().map(Some).collect::<Vec<Option<BundleModule>>>();
+
+    for i in order {
+        let BundleModule { name, program, links } = modules[i].take().unwrap();
+
+        // The entry point of an ES module keeps its exports, they are the exports of the bundle.
+        let exporting = i == 0 && format == Format::Esm;
+        let exports = exports(&program).into_iter().filter(|export| live[i].contains(export)).collect::<Vec<String>>();
+
+        let mut statements = Vec::new();
+
+        for statement in program {
+            statements.extend(transform(statement, &links, &live[i], &externals, format, exporting)
+                .map_err(|err| BundleError { module: i, err })?);
+        }
+
+        let mut compiler = Compiler::new(statements.into_iter());
+        let compiled = compiler.compile().map_err(|err| BundleError { module: i, err })?;
+
+        if i == 0 {
+            parts.push(format!("// {}\n{}", name, compiled));
+        } else {
+            parts.push(format!("// {}\nconst __sol_module_{} = (() => {{\n{}\nreturn {{ {} }};\n}})();", name, i, compiled, exports.join(", ")));
+        }
+    }
+
+    let code = parts.iter().map(|part| part.trim_end()).filter(|part| ! part.is_empty()).collect::<Vec<&str>>().join("\n\n");
+
+    Ok(match format {
+        Format::Esm => format!("{}\n", code),
+        Format::Iife => format!("(() => {{\n{}\n}})();\n", code),
+    })
+}
+
+/// The modules that `module` imports and what they are, in the order of the imports.
+fn uses(module: &BundleModule) -> impl Iterator<Item = (&str, Link)> {
+    module.program.iter().filter_map(|statement| match &statement.kind {
+        Statement::Use { module: name, .. } => Some((name.as_str(), module.links.get(name).cloned().unwrap_or(Link::External))),
+        _ => None,
+    })
+}
+
+/// The order that the modules run in: every module after the modules it imports, and the entry point last.
+fn order(modules: &[BundleModule]) -> Vec<usize> {
+    fn visit(modules: &[BundleModule], i: usize, order: &mut Vec<usize>, visited: &mut HashSet<usize>) {
+        if ! visited.insert(i) {
+            return;
+        }
+
+        for (_, link) in uses(&modules[i]) {
+            if let Link::Module(imported) = link {
+                visit(modules, imported, order, visited);
+            }
+        }
+
+        order.push(i);
+    }
+
+    let mut order = Vec::new();
+
+    visit(modules, 0, &mut order, &mut HashSet::new());
+
+    order
+}
+
+/// The names that a module exports, in the order that they are exported.
+fn exports(program: &Program) -> Vec<String> {
+    let mut exports: Vec<String> = Vec::new();
+
+    for statement in program {
+        let names = match &statement.kind {
+            Statement::Export { declaration: Some(declaration), .. } => match &declaration.kind {
+                Statement::Function { identifier, .. } | Statement::Let { identifier, .. } => vec![identifier.clone()],
+                _ => vec![],
+            },
+            Statement::Export { declaration: None, names } => names.clone(),
+            _ => vec![],
+        };
+
+        for name in names {
+            if ! exports.contains(&name) {
+                exports.push(name);
+            }
+        }
+    }
+
+    exports
+}
+
+/// The top-level function called `name`, whether it's exported or not.
+fn function<'a>(program: &'a Program, name: &str) -> Option<&'a Statement> {
+    program.iter().find_map(|statement| {
+        let declaration = match &statement.kind {
+            Statement::Export { declaration: Some(declaration), .. } => &declaration.kind,
+            kind => kind,
+        };
+
+        match declaration {
+            Statement::Function { identifier, .. } if identifier == name => Some(declaration),
+            _ => None,
+        }
+    })
+}
+
+/// The names that are used by each module, which decides which of its top-level functions, imports and exports
+/// are needed.
+///
+/// Everything that isn't a function declaration runs, so the names it uses are needed. From there, a function
+/// needs the names it uses, and an import needs the export that it imports (or every export, for a namespace).
+/// Names are followed without regard to scope, so a local variable can keep a function with the same name.
+fn live(modules: &[BundleModule], format: Format) -> Vec<HashSet<String>> {
+    let mut live = vec![HashSet::new(); modules.len()];
+    let mut pending = Vec::new();
+
+    for (i, module) in modules.iter().enumerate() {
+        let mut names = Vec::new();
+
+        for statement in module.program.iter() {
+            match &statement.kind {
+                Statement::Function { .. } | Statement::Use { .. } | Statement::Export { declaration: None, .. } => {},
+                Statement::Export { declaration: Some(declaration), .. } if matches!(declaration.kind, Statement::Function { .. }) => {},
+                statement => references(statement, &mut names),
+            }
+        }
+
+        // The exports of the entry point are the exports of the bundle.
+        if i == 0 && format == Format::Esm {
+            names.extend(exports(&module.program));
+        }
+
+        pending.extend(names.into_iter().map(|name| (i, name)));
+    }
+
+    while let Some((i, name)) = pending.pop() {
+        if ! live[i].insert(name.clone()) {
+            continue;
+        }
+
+        let module = &modules[i];
+
+        if let Some(function) = function(&module.program, &name) {
+            let mut names = Vec::new();
+            references(function, &mut names);
+
+            pending.extend(names.into_iter().map(|name| (i, name)));
+        }
+
+        for statement in module.program.iter() {
+            let (imported, imports) = match &statement.kind {
+                Statement::Use { module: imported, imports } => (imported, imports),
+                _ => continue,
+            };
+
+            let imported = match module.links.get(imported) {
+                Some(Link::Module(imported)) => *imported,
+                _ => continue,
+            };
+
+            match imports {
+                Imports::Named(names) => pending.extend(names.iter()
+                    .filter(|import| import.binding() == name)
+                    .map(|import| (imported, import.name.clone()))),
+                Imports::Namespace(namespace) if *namespace == name => pending.extend(exports(&modules[imported].program)
+                    .into_iter()
+                    .map(|export| (imported, export))),
+                _ => {},
+            }
+        }
+    }
+
+    live
+}
+
+/// Add every name that `statement` uses to `names`.
+fn references(statement: &Statement, names: &mut Vec<String>) {
+    match statement {
+        Statement::Let { initial, .. } => expression_references(&initial.kind, names),
+        Statement::Function { parameters, body, .. } => {
+            // Type annotations are checked against the value of the type when the function is called.
+            names.extend(parameters.iter().filter_map(|parameter| parameter.r#type.as_ref().map(|t| t.0.clone())));
+
+            for statement in body {
+                references(&statement.kind, names);
+            }
+        },
+        Statement::If { condition, then, otherwise } => {
+            expression_references(&condition.kind, names);
+
+            for statement in then.iter().chain(otherwise.iter()) {
+                references(&statement.kind, names);
+            }
+        },
+        Statement::While { condition, then } => {
+            expression_references(&condition.kind, names);
+
+            for statement in then {
+                references(&statement.kind, names);
+            }
+        },
+        Statement::Return { expression } | Statement::Expression { expression } => expression_references(&expression.kind, names),
+        Statement::Export { declaration: Some(declaration), .. } => references(&declaration.kind, names),
+        Statement::Export { declaration: None, names: exported } => names.extend(exported.iter().cloned()),
+        Statement::Use { .. } | Statement::Break | Statement::Continue | Statement::Error => {},
+    }
+}
+
+fn expression_references(expression: &Expression, names: &mut Vec<String>) {
+    match expression {
+        Expression::Identifier(name) => names.push(name.clone()),
+        Expression::Array(items) => {
+            for item in items {
+                expression_references(&item.kind, names);
+            }
+        },
+        Expression::Map(members) => {
+            for member in members.values() {
+                expression_references(&member.kind, names);
+            }
+        },
+        Expression::Prefix(_, operand) => expression_references(&operand.kind, names),
+        Expression::Infix(left, _, right) | Expression::Assign(left, right) => {
+            expression_references(&left.kind, names);
+            expression_references(&right.kind, names);
+        },
+        Expression::Call(callee, arguments) => {
+            expression_references(&callee.kind, names);
+
+            for argument in arguments {
+                expression_references(&argument.kind, names);
+            }
+        },
+        Expression::Index(target, index) => {
+            expression_references(&target.kind, names);
+
+            if let Some(index) = index {
+                expression_references(&index.kind, names);
+            }
+        },
+        Expression::Dot(object, property) => {
+            expression_references(&object.kind, names);
+
+            // The name after the dot is a member, not a variable.
+            if ! matches!(property.kind, Expression::Identifier(_)) {
+                expression_references(&property.kind, names);
+            }
+        },
+        Expression::Closure(parameters, body) => {
+            names.extend(parameters.iter().filter_map(|parameter| parameter.r#type.as_ref().map(|t| t.0.clone())));
+
+            for statement in body {
+                references(&statement.kind, names);
+            }
+        },
+        Expression::String(_) | Expression::Number(_) | Expression::Bool(_) => {},
+    }
+}
+
+/// Turn a top-level statement of a module into the statements that it becomes in the bundle.
+///
+/// Imports become variables that are set to the exports of other modules, and exports become plain
+/// declarations unless the module is `exporting`. Functions, imports and exports that aren't `live` are left out.
+fn transform(
+    statement: Node<Statement>, links: &HashMap<String, Link>, live: &HashSet<String>, externals: &[String], format: Format, exporting: bool,
+) -> Result<Vec<Node<Statement>>, CompileError> {
+    let Node { kind, span } = statement;
+
+    match kind {
+        Statement::Use { module, imports } => {
+            let object = match links.get(&module) {
+                Some(Link::Module(i)) => format!("__sol_module_{}", i),
+                _ if format == Format::Iife => {
+                    return Err(CompileError::unsupported(span, format!("importing the external module `{}` in an IIFE bundle", module)));
+                },
+                _ => format!("__sol_external_{}", externals.iter().position(|external| *external == module).unwrap()),
+            };
+
+            let bindings = match imports {
+                Imports::Named(names) => names.into_iter()
+                    .filter(|import| live.contains(import.binding()))
+                    .map(|import| {
+                        let member = Expression::Dot(Expression::Identifier(object.clone()).boxed(), Expression::Identifier(import.name.clone()).boxed());
+
+                        (import.binding().to_owned(), member)
+                    })
+                    .collect(),
+                Imports::Namespace(namespace) if live.contains(&namespace) => vec![(namespace, Expression::Identifier(object))],
+                _ => vec![],
+            };
+
+            Ok(bindings.into_iter().map(|(identifier, initial)| {
+                Node::new(Statement::Let { identifier, r#type: None, initial: Node::new(initial, span), doc: None }, span)
+            }).collect())
+        },
+        Statement::Function { ref identifier, .. } if ! live.contains(identifier) => Ok(vec![]),
+        Statement::Export { declaration: Some(declaration), names } => {
+            if exporting {
+                return Ok(vec![Node::new(Statement::Export { declaration: Some(declaration), names }, span)]);
+            }
+
+            transform(*declaration, links, live, externals, format, exporting)
+        },
+        Statement::Export { declaration: None, names } if exporting => Ok(vec![Node::new(Statement::Export { declaration: None, names }, span)]),
+        Statement::Export { declaration: None, .. } => Ok(vec![]),
+        kind => Ok(vec![Node::new(kind, span)]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sol_parser::parse;
+
+    fn module(name: &str, source: &str, links: &[(&str, Link)]) -> BundleModule {
+        BundleModule {
+            name: name.to_owned(),
+            program: parse(source).unwrap(),
+            links: links.iter().cloned().map(|(name, link)| (name.to_owned(), link)).collect(),
+        }
+    }
+
+    fn modules() -> Vec<BundleModule> {
+        vec![
+            module("main.sol", "import area from \"./shapes\"\nimport * as colors from \"./shapes\"\nfn unused() { return 1 }\nexport let size = area(2)\nlet hex = colors.red", &[
+                ("./shapes", Link::Module(1)),
+            ]),
+            module("shapes.sol", "import { scale } from \"./math\"\nexport fn area(n) { return scale(n * n) }\nexport fn perimeter(n) { return n * 4 }\nexport let red = \"#f00\"", &[
+                ("./math", Link::Module(2)),
+            ]),
+            module("math.sol", "export fn scale(n) { return n * 2 }\nexport fn unused(n) { return n }", &[]),
+        ]
+    }
+
+    #[test]
+    fn tree_shakes_unused_functions() {
+        let live = live(&modules(), Format::Esm);
+
+        assert!(live[0].contains("size") && ! live[0].contains("unused"));
+        // Importing `colors` as a namespace keeps every export of `shapes.sol`.
+        assert!(live[1].contains("perimeter") && live[1].contains("scale"));
+        assert!(live[2].contains("scale") && ! live[2].contains("unused"));
+
+        let bundled = bundle(modules(), Format::Esm, "").unwrap();
+
+        assert!(! bundled.contains("unused"), "{}", bundled);
+        assert!(bundled.find("// math.sol").unwrap() < bundled.find("// shapes.sol").unwrap(), "{}", bundled);
+        assert!(bundled.contains("return { area, perimeter, red };"), "{}", bundled);
+        assert!(bundled.contains("export let size"), "{}", bundled);
+    }
+
+    #[test]
+    fn imports_externals() {
+        let modules = vec![module("main.sol", "import read from \"fs\"\nimport \"./side\"\nread(\"a\")", &[])];
+        let bundled = bundle(modules, Format::Esm, "").unwrap();
+
+        assert!(bundled.starts_with("import * as __sol_external_0 from \"fs\";\n"), "{}", bundled);
+        assert!(bundled.contains("let read = __sol_external_0.read;"), "{}", bundled);
+
+        let modules = vec![module("main.sol", "import read from \"fs\"", &[])];
+        let err = bundle(modules, Format::Iife, "").unwrap_err();
+
+        assert_eq!(err.module, 0);
+        assert!(matches!(err.err, CompileError::Unsupported { .. }));
+    }
+
+    #[test]
+    fn runs_iife_bundles() {
+        let mut modules = modules();
+        modules[0].program.extend(parse("size + colors.perimeter(1) + colors.red").unwrap());
+
+        let bundled = bundle(modules, Format::Iife, "").unwrap();
+
+        assert!(! bundled.contains("export"), "{}", bundled);
+
+        let runtime = rquickjs::Runtime::new().unwrap();
+        let context = rquickjs::Context::full(&runtime).unwrap();
+
+        // The value of the bundle is `undefined`, so the result is read back from a global.
+        let bundled = bundled.replace("size + colors.perimeter(1) + colors.red;", "globalThis.result = size + colors.perimeter(1) + colors.red;");
+
+        context.with(|ctx| {
+            ctx.eval::<(), _>(bundled.as_str()).unwrap_or_else(|e| panic!("{}: {:?}", bundled, e));
+
+            assert_eq!(ctx.globals().get::<_, String>("result").unwrap(), "12#f00");
+        });
+    }
+}
@@ -1,32 +1,49 @@
-use sol_parser::{Statement, Expression, Op};
-use sol_js_builder::{Builder, Var, While, IfElse, Function, Expression as JsExpression};
+use sol_parser::{Statement, Expression, Op, Node, Imports};
+use crate::CompileError;
+use sol_js_builder::{Builder, Var, While, IfElse, Function, TryCatch, Import, Expression as JsExpression};
 use std::vec::IntoIter;
 
+type CompileResult<T> = Result<T, CompileError>;
+
 #[derive(Debug)]
 pub(crate) struct Compiler {
-    ast: IntoIter<Statement>,
+    ast: IntoIter<Node<Statement>>,
     builder: Builder,
 }
 
 impl Compiler {
-    pub fn new(ast: IntoIter<Statement>) -> Self {
+    pub fn new(ast: IntoIter<Node<Statement>>) -> Self {
         Self {
             ast,
             builder: Builder::new(),
         }
     }
 
-    pub fn compile_statement(&mut self, statement: Statement) {
-        match statement {
+    pub fn compile_statement(&mut self, statement: Node<Statement>) -> CompileResult<()> {
+        // Statements generated by the compiler have an empty span and aren't mapped.
+        if statement.span.line > 0 {
+            self.builder.map(statement.span.line - 1, statement.span.column - 1);
+        }
+
+        match statement.kind {
             Statement::Use { module, imports } => {
-                self.builder.import(imports, module);
+                self.builder.import(import(module, &imports));
+            },
+            Statement::Export { declaration: Some(declaration), .. } => {
+                let mut declaration = Compiler::new(vec![*declaration].into_iter());
+                declaration.compile()?;
+
+                self.builder.export(declaration.builder());
+            },
+            Statement::Export { declaration: None, names } => {
+                self.builder.export_names(names);
             },
             Statement::Let { identifier, initial, .. } => {
                 let mut var = Var::new();
                 
                 var.id(identifier)
                     .as_let()
-                    .value(self.compile_expression(initial));
+                    .value(self.compile_expression(initial)?);
 
                 self.builder.var(var);
             },
@@ -39,17 +56,17 @@ impl Compiler {
                     if let Some(sol_parser::Type(typed)) = &parameter.r#type {
                         body.compile_statement(Statement::Expression {
                             expression: Expression::Call(
-                                Box::new(Expression::Identifier("__sol_assert_type".to_owned())),
+                                Expression::Identifier("__sol_assert_type".to_owned()).boxed(),
                                 vec![
-                                    Expression::Identifier(parameter.name.clone()),
-                                    Expression::Identifier(typed.clone())
+                                    Expression::Identifier(parameter.name.clone()).into(),
+                                    Expression::Identifier(typed.clone()).into()
                                 ]
-                            )
-                        });
+                            ).into()
+                        }.into())?;
                     }
                 }
 
-                body.compile();
+                body.compile()?;
 
                 function
                     .id(identifier)
@@ -61,13 +78,13 @@ impl Compiler {
                 self.builder.function(function);
             },
             Statement::Return { expression } => {
-                let expression = self.compile_expression(expression);
+                let expression = self.compile_expression(expression)?;
                 self.builder.return_(Some(expression));
             },
             Statement::While { condition, then } => {
-                let condition = self.compile_expression(condition);
+                let condition = self.compile_expression(condition)?;
                 let mut then = Compiler::new(then.into_iter());
-                then.compile();
+                then.compile()?;
 
                 let mut while_ = While::new(condition);
                 while_.then(then.builder());
@@ -75,10 +92,10 @@ impl Compiler {
                 self.builder.while_loop(while_);
             },
             Statement::If { condition, then, otherwise } => {
-                let condition = self.compile_expression(condition);
+                let condition = self.compile_expression(condition)?;
 
                 let mut then = Compiler::new(then.into_iter());
-                then.compile();
+                then.compile()?;
 
                 let mut if_ = IfElse::new(condition);
                 if_
@@ -86,7 +103,7 @@ impl Compiler {
 
                 if ! otherwise.is_empty() {
                     let mut otherwise = Compiler::new(otherwise.into_iter());
-                    otherwise.compile();
+                    otherwise.compile()?;
 
                     if_.otherwise(otherwise.builder());
                 }
@@ -94,31 +111,41 @@ impl Compiler {
                 self.builder.conditional(if_);
             },
             Statement::Expression { expression } => {
-                let expression = self.compile_expression(expression);
+                let expression = self.compile_expression(expression)?;
 
                 self.builder.expression(expression);
             },
-            _ => unimplemented!("compile statement {:?}", statement),
+            Statement::Break => {
+                self.builder.break_();
+            },
+            Statement::Continue => {
+                self.builder.continue_();
+            },
+            Statement::Error => return Err(CompileError::unsupported(statement.span, "a statement that failed to parse")),
         }
+
+        Ok(())
     }
 
-    pub fn compile_expression(&mut self, expression: Expression) -> JsExpression {
+    pub fn compile_expression(&mut self, expression: Node<Expression>) -> CompileResult<JsExpression> {
         use std::collections::HashMap;
 
-        match expression {
+        let span = expression.span;
+
+        Ok(match expression.kind {
             Expression::String(s) => s.into(),
             Expression::Number(n) => n.into(),
             Expression::Bool(b) => b.into(),
-            Expression::Array(items) => items.into_iter().map(|i| self.compile_expression(i)).collect::<Vec<JsExpression>>().into(),
+            Expression::Array(items) => items.into_iter().map(|i| self.compile_expression(i)).collect::<CompileResult<Vec<JsExpression>>>()?.into(),
             Expression::Map(members) => {
-                let members = members.into_iter().map(|(k, v)| (k, self.compile_expression(v))).collect::<HashMap<String, JsExpression>>();
+                let members = members.into_iter().map(|(k, v)| Ok((k, self.compile_expression(v)?))).collect::<CompileResult<HashMap<String, JsExpression>>>()?;
 
                 JsExpression::Object(members)
             },
             Expression::Identifier(i) => JsExpression::identifier(i),
             Expression::Infix(left, op, right) => {
                 JsExpression::from((
-                    self.compile_expression(*left),
+                    self.compile_expression(*left)?,
                     (match op {
                         Op::GreaterThan => ">",
                         Op::LessThan => "<",
@@ -128,6 +155,7 @@ impl Compiler {
                         Op::Subtract => "-",
                         Op::Multiply => "*",
                         Op::Divide => "/",
+                        Op::Power => "**",
                         Op::Equals => "===",
                         Op::NotEquals => "!==",
                         Op::And => "&&",
@@ -137,28 +165,28 @@ impl Compiler {
                         Op::SubtractAssign => "-=",
                         Op::MultiplyAssign => "*=",
                         Op::DivideAssign => "/=",
-                        _ => unimplemented!(),
+                        _ => return Err(CompileError::unsupported(span, format!("the infix operator {:?}", op))),
                     }).to_string(),
-                    self.compile_expression(*right),
+                    self.compile_expression(*right)?,
                 ))
             },
             Expression::Call(callable, args) => {
                 JsExpression::Call(
-                    Box::new(self.compile_expression(*callable)),
-                    args.into_iter().map(|a| self.compile_expression(a)).collect::<Vec<JsExpression>>()
+                    Box::new(self.compile_expression(*callable)?),
+                    args.into_iter().map(|a| self.compile_expression(a)).collect::<CompileResult<Vec<JsExpression>>>()?
                 )
             },
             Expression::Assign(target, value) => {
                 // TODO: Add support for more convenient assignment operators - `+=`, `-=`, `*=`, etc.
-                JsExpression::infix(self.compile_expression(*target), "=", self.compile_expression(*value))
+                JsExpression::infix(self.compile_expression(*target)?, "=", self.compile_expression(*value)?)
             },
             Expression::Index(array, index) => {
                 // If we're appending a value, i.e. `items[] = ...`, we don't want to use the normal syntax and instead
                 // want to meta-program a `.length` index so that the value is added to the end of the array.
                 if let Some(index) = index {
-                    JsExpression::index(self.compile_expression(*array), self.compile_expression(*index))
+                    JsExpression::index(self.compile_expression(*array)?, self.compile_expression(*index)?)
                 } else {
-                    let array = self.compile_expression(*array);
+                    let array = self.compile_expression(*array)?;
 
                     JsExpression::index(
                         array.clone(),
@@ -171,13 +199,13 @@ impl Compiler {
             },
             Expression::Dot(object, property) => {
                 JsExpression::dot(
-                    self.compile_expression(*object),
-                    self.compile_expression(*property)
+                    self.compile_expression(*object)?,
+                    self.compile_expression(*property)?
                 )
             },
             Expression::Closure(parameters, body) => {
                 let mut body = Compiler::new(body.into_iter());
-                body.compile();
+                body.compile()?;
 
                 JsExpression::closure(
                     parameters.into_iter().map(|p| JsExpression::identifier(p.name)).collect::<Vec<JsExpression>>(),
@@ -188,22 +216,119 @@ impl Compiler {
                 JsExpression::Prefix(match op {
                     Op::Not => "!".to_owned(),
                     Op::Subtract => "-".to_owned(),
-                    _ => unreachable!()
-                }, Box::new(self.compile_expression(*value)))
+                    _ => return Err(CompileError::unsupported(span, format!("the prefix operator {:?}", op))),
+                }, Box::new(self.compile_expression(*value)?))
             },
-            _ => unimplemented!("compile expression {:?}", expression),
-        }
+        })
     }
 
-    pub fn compile(&mut self) -> String {
+    pub fn compile(&mut self) -> CompileResult<String> {
         while let Some(statement) = self.ast.next() {
-            self.compile_statement(statement);
+            self.compile_statement(statement)?;
         }
 
-        self.builder.source()
+        Ok(self.builder.source())
+    }
+
+    /// Compile a single input of an interactive session, see `sol_compiler::compile_interactive`.
+    pub fn compile_interactive(&mut self) -> CompileResult<String> {
+        let global = |name: &str| JsExpression::dot(JsExpression::identifier("globalThis"), JsExpression::identifier(name));
+
+        // Imports have to stay at the top level of the module, outside of the `try` block.
+        let mut imports = Builder::new();
+        let statements = self.ast.by_ref().collect::<Vec<Node<Statement>>>();
+        let last = statements.len().saturating_sub(1);
+
+        for (i, statement) in statements.into_iter().enumerate() {
+            // Nothing can import an interactive session, so exported declarations are declared like any other.
+            let Node { kind, span } = match statement.kind {
+                Statement::Export { declaration: Some(declaration), .. } => *declaration,
+                Statement::Export { declaration: None, .. } => continue,
+                _ => statement,
+            };
+
+            match kind {
+                Statement::Use { module, imports: names } => {
+                    imports.import(import(module, &names));
+
+                    for name in names.bindings() {
+                        self.builder.expression(JsExpression::infix(global(name), "=", JsExpression::identifier(name)));
+                    }
+                },
+                Statement::Let { identifier, initial, .. } => {
+                    let initial = self.compile_expression(initial)?;
+
+                    self.builder.expression(JsExpression::infix(global(&identifier), "=", initial));
+                },
+                Statement::Function { identifier, parameters, return_type, body, doc } => {
+                    self.compile_statement(Node::new(Statement::Function { identifier: identifier.clone(), parameters, return_type, body, doc }, span))?;
+                    self.builder.expression(JsExpression::infix(global(&identifier), "=", JsExpression::identifier(identifier)));
+                },
+                Statement::Expression { expression } if i == last => {
+                    let expression = self.compile_expression(expression)?;
+
+                    self.builder.expression(JsExpression::infix(global("__sol_value"), "=", expression));
+                },
+                kind => self.compile_statement(Node::new(kind, span))?,
+            }
+        }
+
+        let mut handler = Builder::new();
+        handler.expression(JsExpression::infix(global("__sol_uncaught"), "=", JsExpression::identifier("e")));
+
+        let mut try_catch = TryCatch::new(self.builder());
+        try_catch.catch("e".to_owned(), handler);
+
+        imports.try_catch(try_catch);
+
+        Ok(imports.source())
     }
 
     pub fn builder(&self) -> Builder {
         self.builder.clone()
     }
-}
\ No newline at end of file
+}
+
+fn import(module: String, imports: &Imports) -> Import {
+    let mut import = Import::new(module);
+
+    match imports {
+        Imports::Named(names) => {
+            for name in names {
+                import.name(name.name.clone(), name.alias.clone());
+            }
+        },
+        Imports::Namespace(namespace) => {
+            import.namespace(namespace.clone());
+        },
+        Imports::Bare => {},
+    }
+
+    import
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sol_parser::{FileId, Span};
+
+    #[test]
+    fn unsupported_constructs() {
+        let span = Span::new(FileId(0), 4, 9, 1, 5);
+        let ast = vec![
+            Node::new(Statement::Expression {
+                expression: Node::new(Expression::Infix(Expression::Bool(true).boxed(), Op::Not, Expression::Bool(false).boxed()), span),
+            }, span),
+        ];
+
+        let err = Compiler::new(ast.into_iter()).compile().unwrap_err();
+
+        assert!(matches!(&err, CompileError::Unsupported { span: s, .. } if *s == span));
+        assert_eq!(err.to_string(), "the infix operator Not cannot be compiled to JavaScript on line 1, column 5");
+
+        let ast = vec![Node::new(Statement::Error, span)];
+        let err = Compiler::new(ast.into_iter()).compile().unwrap_err();
+
+        assert_eq!(err.diagnostics()[0].message, "a statement that failed to parse cannot be compiled to JavaScript");
+    }
+}
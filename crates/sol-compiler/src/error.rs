@@ -0,0 +1,57 @@
+use sol_parser::{Diagnostic, ParserError, Span};
+use std::fmt::{Display, Formatter};
+
+/// The reasons that `compile` can fail.
+#[derive(Debug, Clone)]
+pub enum CompileError {
+    /// The source code could not be parsed. Every error that the parser found is included.
+    Parse(Vec<ParserError>),
+    /// The source code is valid, but uses a construct that can't be compiled to JavaScript.
+    Unsupported {
+        span: Span,
+        construct: String,
+    },
+}
+
+impl CompileError {
+    pub(crate) fn unsupported(span: Span, construct: impl Into<String>) -> Self {
+        Self::Unsupported { span, construct: construct.into() }
+    }
+
+    /// Convert the error into a `Diagnostic` for each problem, ready to be rendered against the source code.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            CompileError::Parse(errors) => errors.iter().map(Diagnostic::from).collect(),
+            CompileError::Unsupported { span, construct } => vec![
+                Diagnostic::new(format!("{} cannot be compiled to JavaScript", construct), *span),
+            ],
+        }
+    }
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompileError::Parse(errors) => {
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+
+                    write!(f, "{}", error)?;
+                }
+
+                Ok(())
+            },
+            CompileError::Unsupported { span, construct } => write!(f, "{} cannot be compiled to JavaScript on line {}, column {}", construct, span.line, span.column),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+impl From<Vec<ParserError>> for CompileError {
+    fn from(errors: Vec<ParserError>) -> Self {
+        Self::Parse(errors)
+    }
+}
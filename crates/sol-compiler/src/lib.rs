@@ -1,22 +1,258 @@
-use sol_parser::{parse, Lexer};
-pub use sol_parser::{Token, TokenKind};
+pub use sol_parser::{parse, format, Json, Program, Statement, Imports, Node, Lexer, KEYWORDS, Token, TokenKind, LexError, LexErrorType, ParserError, Diagnostic, Span};
+pub use sol_js_builder::{SourceMap, Mapping};
+pub use error::CompileError;
+pub use bundle::{bundle, BundleError, BundleModule, Format, Link};
 
+mod bundle;
 mod compiler;
+mod error;
 
-pub fn compile(source: &str) -> String {
-    let ast = parse(source).unwrap();
+pub fn compile(source: &str) -> Result<String, CompileError> {
+    let ast = parse(source)?;
 
     let mut compiler = compiler::Compiler::new(ast.into_iter());
     compiler.compile()
 }
 
-pub fn lex(source: &str) -> Vec<Token> {
-    let mut lexer = Lexer::new(source);
-    let mut tokens = Vec::new();
+/// Compile the Sol source code from `file`, along with a source map that points back at it.
+pub fn compile_with_source_map(source: &str, file: &str) -> Result<(String, SourceMap), CompileError> {
+    let ast = parse(source)?;
 
-    while let Some(token) = lexer.next() {
-        tokens.push(token);
+    let mut compiler = compiler::Compiler::new(ast.into_iter());
+    let compiled = compiler.compile()?;
+
+    Ok((compiled, compiler.builder().source_map(file)))
+}
+
+/// Compile a single input of an interactive session into an ES module.
+/// 
+/// Top-level bindings and imports are stored on `globalThis`, so that later inputs can use them, and the value
+/// of a trailing expression statement is stored in `globalThis.__sol_value`. Exceptions are caught and stored
+/// in `globalThis.__sol_uncaught`, since only the message and stack trace are left once they reach Rust.
+pub fn compile_interactive(source: &str) -> Result<String, CompileError> {
+    let ast = parse(source)?;
+
+    let mut compiler = compiler::Compiler::new(ast.into_iter());
+    compiler.compile_interactive()
+}
+
+pub fn lex(source: &str) -> Result<Vec<Token>, LexError> {
+    Lexer::new(source).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compiles() {
+        assert_eq!(compile("let a = 1").unwrap(), "let a = 1;");
+    }
+
+    #[test]
+    fn power() {
+        assert_eq!(compile("let a = 2 ** 3").unwrap(), "let a = 2 ** 3;");
+        assert_eq!(run("let a = 2 ** 3\n\"\" + a"), "8");
+        assert_eq!(run("let a = 2 ** 3 ** 2\n\"\" + a"), "512");
+        assert_eq!(run("let a = 2 * 3 ** 2\n\"\" + a"), "18");
+        assert_eq!(run("let a = -2 ** 2\n\"\" + a"), "4");
+    }
+
+    #[test]
+    fn imports() {
+        assert_eq!(
+            compile("import a, b as c from \"m\"\nimport * as n from \"m\"\nimport \"m\"").unwrap(),
+            "import { a, b as c } from \"m\";\nimport * as n from \"m\";\nimport \"m\";\n",
+        );
+    }
+
+    #[test]
+    fn exports() {
+        let compiled = compile("let a = 1\nexport { a }\nexport fn f() {}\nexport let b = 2").unwrap();
+
+        assert!(compiled.starts_with("export function f"), "{}", compiled);
+        assert!(compiled.contains("let a = 1;\nexport { a };\nexport let b = 2;"), "{}", compiled);
     }
 
-    tokens
-}
\ No newline at end of file
+    /// Compile and run `source` in QuickJS, returning the value of the final expression as a string.
+    fn run(source: &str) -> String {
+        let compiled = compile(source).unwrap_or_else(|e| panic!("{}", e));
+
+        let runtime = rquickjs::Runtime::new().unwrap();
+        let context = rquickjs::Context::full(&runtime).unwrap();
+
+        context.with(|ctx| ctx.eval::<String, _>(compiled.as_str()).unwrap_or_else(|e| panic!("{}: {:?}", compiled, e)))
+    }
+
+    #[test]
+    fn break_and_continue() {
+        assert_eq!(run("
+            let i = 0
+            let out = []
+
+            while i < 10 {
+                i = i + 1
+
+                if i % 2 == 0 {
+                    continue
+                }
+
+                if i > 7 {
+                    break
+                }
+
+                out[] = i
+            }
+
+            out.join(\",\")
+        "), "1,3,5,7");
+    }
+
+    #[test]
+    fn nested_break_and_continue() {
+        assert_eq!(run("
+            let i = 0
+            let pairs = []
+
+            while i < 3 {
+                i = i + 1
+
+                let j = 0
+
+                while true {
+                    j = j + 1
+
+                    if j > i {
+                        break
+                    }
+
+                    if j == 2 {
+                        continue
+                    }
+
+                    pairs[] = i * 10 + j
+                }
+
+                if i == 2 {
+                    continue
+                }
+
+                pairs[] = 0
+            }
+
+            pairs.join(\",\")
+        "), "11,0,21,31,33,0");
+    }
+
+    #[test]
+    fn break_inside_function_inside_loop() {
+        assert_eq!(run("
+            fn first_over(items, limit) {
+                let found = -1
+                let i = 0
+
+                while i < items.length {
+                    if items[i] > limit {
+                        found = items[i]
+                        break
+                    }
+
+                    i = i + 1
+                }
+
+                return found
+            }
+
+            let out = []
+            let n = 0
+
+            while n < 2 {
+                n = n + 1
+                out[] = first_over([1, 5, 9], n * 4)
+            }
+
+            out.join(\",\")
+        "), "5,9");
+    }
+
+    #[test]
+    fn source_maps() {
+        let (compiled, map) = compile_with_source_map("let a = 1\n\nfn f(x) {\n    return x.missing.value\n}\n\nf(a)", "main.sol").unwrap();
+
+        let runtime = rquickjs::Runtime::new().unwrap();
+        let context = rquickjs::Context::full(&runtime).unwrap();
+
+        let stack = context.with(|ctx| match ctx.compile("main.js", compiled.as_str()) {
+            Err(rquickjs::Error::Exception { stack, .. }) => stack,
+            result => panic!("expected an exception, found {:?}", result.map(|_| ())),
+        });
+
+        // QuickJS only reports lines, e.g. `at f (main.js:2)`.
+        let line = stack.lines().next().unwrap().rsplit(':').next().unwrap().trim_end_matches(')').parse::<usize>().unwrap();
+        let mapping = map.lookup(line - 1, 0).unwrap();
+
+        assert_eq!((mapping.source_line, mapping.source_column), (3, 4));
+        assert_eq!(map.source, "main.sol");
+    }
+
+    #[test]
+    fn closure_source_maps() {
+        let (compiled, map) = compile_with_source_map("let f = fn (x) {\n    return x.missing.value\n}\n\nf(1)", "main.sol").unwrap();
+
+        let runtime = rquickjs::Runtime::new().unwrap();
+        let context = rquickjs::Context::full(&runtime).unwrap();
+
+        let stack = context.with(|ctx| match ctx.compile("main.js", compiled.as_str()) {
+            Err(rquickjs::Error::Exception { stack, .. }) => stack,
+            result => panic!("expected an exception, found {:?}", result.map(|_| ())),
+        });
+
+        // The frame of the closure points into its body, not at the `let` that declares it.
+        let line = stack.lines().next().unwrap().rsplit(':').next().unwrap().trim_end_matches(')').parse::<usize>().unwrap();
+        let mapping = map.lookup(line - 1, 0).unwrap();
+
+        assert_eq!((mapping.source_line, mapping.source_column), (1, 4));
+    }
+
+    #[test]
+    fn interactive() {
+        let runtime = rquickjs::Runtime::new().unwrap();
+        let context = rquickjs::Context::full(&runtime).unwrap();
+
+        context.with(|ctx| {
+            let eval = |source: &str| {
+                ctx.globals().set("__sol_value", rquickjs::Undefined).unwrap();
+                ctx.compile("repl", compile_interactive(source).unwrap()).unwrap();
+                ctx.globals().get::<_, Option<f64>>("__sol_value").unwrap()
+            };
+
+            assert_eq!(eval("let a = 1"), None);
+            assert_eq!(eval("fn double(n) { return n * 2 }"), None);
+            assert_eq!(eval("let a = double(a) + 1"), None);
+            assert_eq!(eval("let b = 1\nb + a"), Some(4.0));
+            assert_eq!(eval("export let c = 2\nexport { c }\nc + b"), Some(3.0));
+
+            ctx.compile("repl", compile_interactive("undefined.property").unwrap()).unwrap();
+
+            let uncaught = ctx.globals().get::<_, rquickjs::Object>("__sol_uncaught").unwrap();
+
+            assert_eq!(uncaught.get::<_, String>("name").unwrap(), "TypeError");
+        });
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = compile("let = 1\nlet b = )").unwrap_err();
+
+        match &err {
+            CompileError::Parse(errors) => assert_eq!(errors.len(), 2),
+            _ => panic!("expected a parse error, found {:?}", err),
+        }
+
+        assert_eq!(err.diagnostics().iter().map(|d| d.span.line).collect::<Vec<usize>>(), vec![1, 2]);
+        assert_eq!(err.to_string(), [
+            "expected an identifier but found `=` on line 1, column 5",
+            "expected an expression but found `)` on line 2, column 9",
+        ].join("\n"));
+    }
+}
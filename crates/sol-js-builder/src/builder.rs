@@ -1,74 +1,146 @@
-use crate::{Var, Expression, Function, IfElse, While};
+use crate::{Var, Expression, Function, IfElse, While, TryCatch, Import, Mapping, SourceMap};
 use std::fmt::{Result, Formatter, Display};
 
 #[derive(Debug, Clone)]
 pub struct Builder {
     source: String,
+    mappings: Vec<Mapping>,
+    /// The line and column (in UTF-16 code units) at the end of `source`, kept up to date by `push`.
+    line: usize,
+    column: usize,
 }
 
 impl Builder {
     pub fn new() -> Self {
         Self {
             source: String::default(),
+            mappings: Vec::new(),
+            line: 0,
+            column: 0,
         }
     }
 
-    pub fn import(&mut self, items: Vec<String>, module: String) -> &mut Self {
-        self.source.push_str("import { ");
-        self.source.push_str(&items.join(", "));
-        self.source.push_str(" } from \"");
-        self.source.push_str(&module);
-        self.source.push_str("\";\n");
+    /// Record that the code added next was generated from `line` and `column` of the source file, both
+    /// starting at 0.
+    /// 
+    /// Mapped code always starts on a new line, since some engines only report line numbers in stack traces.
+    pub fn map(&mut self, line: usize, column: usize) -> &mut Self {
+        if self.column > 0 {
+            self.push("\n");
+        }
+
+        self.mappings.push(Mapping { generated_line: self.line, generated_column: self.column, source_line: line, source_column: column });
+
+        self
+    }
+
+    /// Add `code` to the source, moving the position that the next piece of code is added at past it.
+    fn push(&mut self, code: &str) {
+        match code.rfind('\n') {
+            Some(last) => {
+                self.line += code.matches('\n').count();
+                self.column = code[last + 1..].encode_utf16().count();
+            },
+            None => self.column += code.encode_utf16().count(),
+        }
+
+        self.source.push_str(code);
+    }
+
+    /// Add `code` along with the mappings of any builders nested inside of it, which are relative to the start of `code`.
+    fn append(&mut self, code: &str, mappings: Vec<Mapping>) {
+        let (line, column) = (self.line, self.column);
+
+        self.mappings.extend(mappings.into_iter().map(|m| Mapping {
+            generated_line: line + m.generated_line,
+            generated_column: if m.generated_line == 0 { column + m.generated_column } else { m.generated_column },
+            ..m
+        }));
+
+        self.push(code);
+    }
+
+    /// The mappings of this builder, for a body that starts on `line` of the code it is nested in.
+    pub(crate) fn mappings_from(&self, line: usize) -> Vec<Mapping> {
+        self.mappings.iter().map(|m| Mapping { generated_line: line + m.generated_line, ..*m }).collect()
+    }
+
+    pub(crate) fn lines(&self) -> usize {
+        self.line
+    }
+
+    pub fn import(&mut self, import: Import) -> &mut Self {
+        self.push(&import.to_string());
+        self
+    }
+
+    /// Export the declarations in `declaration`, e.g. `export function f() {}`.
+    pub fn export(&mut self, declaration: Builder) -> &mut Self {
+        self.push("export ");
+        self.append(&declaration.source, declaration.mappings);
+        self
+    }
+
+    /// Export bindings that are declared elsewhere in the module, e.g. `export { a, b };`.
+    pub fn export_names(&mut self, names: Vec<String>) -> &mut Self {
+        self.push("export { ");
+        self.push(&names.join(", "));
+        self.push(" };");
 
         self
     }
 
     pub fn var(&mut self, var: Var) -> &mut Self {
-        self.source.push_str(&var.to_string());
+        self.append(&var.to_string(), var.mappings());
         self
     }
 
-    pub fn function(&mut self, function: Function) -> &mut Self {        
-        self.source.push_str(&function.to_string());
+    pub fn function(&mut self, function: Function) -> &mut Self {
+        self.append(&function.to_string(), function.mappings());
         self
     }
 
     pub fn conditional(&mut self, if_else: IfElse) -> &mut Self {
-        self.source.push_str(&if_else.to_string());
+        self.append(&if_else.to_string(), if_else.mappings());
         self
     }
 
     pub fn while_loop(&mut self, while_: While) -> &mut Self {
-        self.source.push_str(&while_.to_string());
+        self.append(&while_.to_string(), while_.mappings());
+        self
+    }
+
+    pub fn try_catch(&mut self, try_catch: TryCatch) -> &mut Self {
+        self.append(&try_catch.to_string(), try_catch.mappings());
         self
     }
 
     pub fn return_(&mut self, expression: Option<Expression>) -> &mut Self {
-        self.source.push_str("return");
+        self.push("return");
 
         if let Some(expression) = expression {
-            self.source.push(' ');
-            self.source.push_str(&expression.to_string());
+            self.push(" ");
+            self.append(&expression.to_string(), expression.mappings());
         }
 
-        self.source.push(';');
+        self.push(";");
 
         self
     }
 
     pub fn break_(&mut self) -> &mut Self {
-        self.source.push_str("break;");
+        self.push("break;");
         self
     }
 
     pub fn continue_(&mut self) -> &mut Self {
-        self.source.push_str("continue;");
+        self.push("continue;");
         self
     }
 
     pub fn expression(&mut self, expression: Expression) -> &mut Self {
-        self.source.push_str(&expression.to_string());
-        self.source.push(';');
+        self.append(&expression.to_string(), expression.mappings());
+        self.push(";");
 
         self
     }
@@ -76,6 +148,11 @@ impl Builder {
     pub fn source(&self) -> String {
         self.source.clone()
     }
+
+    /// Create a source map for the generated code, which was compiled from the `source` file.
+    pub fn source_map(&self, source: impl Into<String>) -> SourceMap {
+        SourceMap::new(source, self.mappings.clone())
+    }
 }
 
 impl Display for Builder {
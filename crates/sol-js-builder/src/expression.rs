@@ -1,6 +1,7 @@
 use std::fmt::{Display, Formatter, Result};
 use std::collections::HashMap;
-use crate::Builder;
+use crate::{Builder, Mapping};
+use crate::precedence::{self, Associativity, Precedence};
 
 #[derive(Debug, Clone)]
 pub enum Expression {
@@ -127,10 +128,59 @@ impl From<(Box<Expression>, Vec<Expression>)> for Expression {
     }
 }
 
+impl Expression {
+    /// How tightly this expression binds when it appears as the operand of another expression.
+    fn precedence(&self) -> Precedence {
+        match self {
+            // A negative literal is printed with a leading `-`, so it behaves like a prefix operation.
+            Expression::Number(n) if n.is_sign_negative() => precedence::PREFIX,
+            Expression::Infix(_, op, _) => precedence::infix(op).map(|(p, _)| p).unwrap_or(0),
+            Expression::Prefix(..) => precedence::PREFIX,
+            Expression::Index(..) | Expression::Dot(..) | Expression::Call(..) => precedence::POSTFIX,
+            Expression::Closure(..) => precedence::ASSIGNMENT,
+            _ => precedence::PRIMARY,
+        }
+    }
+
+    /// The mappings of the closures in the expression, relative to the start of its code.
+    pub(crate) fn mappings(&self) -> Vec<Mapping> {
+        match self {
+            Expression::Closure(_, body) => body.mappings_from(1),
+            Expression::Array(items) => nested(items.iter(), 0, 0),
+            // Every member is written on a line of its own, after the `{`.
+            Expression::Object(members) => nested(members.values(), 1, 1),
+            Expression::Index(left, right) | Expression::Dot(left, right) | Expression::Infix(left, _, right) => {
+                nested([&**left, &**right].into_iter(), 0, 0)
+            },
+            Expression::Prefix(_, right) => right.mappings(),
+            Expression::Call(callable, parameters) => nested(std::iter::once(&**callable).chain(parameters.iter()), 0, 0),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Render the expression, wrapping it in parentheses when it binds looser than `min`.
+    fn operand(&self, min: Precedence) -> String {
+        if self.precedence() < min {
+            format!("({})", self)
+        } else {
+            self.to_string()
+        }
+    }
+
+    /// Render the expression as the target of a `.`, `[]` or `()` postfix operation.
+    fn target(&self) -> String {
+        match self {
+            // `1.length` would be read as a malformed number literal, so integer targets are wrapped.
+            Expression::Number(n) if ! n.to_string().contains('.') => format!("({})", self),
+            _ => self.operand(precedence::POSTFIX),
+        }
+    }
+}
+
 impl Display for Expression {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(f, "{}", match self {
-            Expression::String(s) => format!(r##""{}""##, s),
+            Expression::String(s) => quote(s),
             Expression::Number(n) => n.to_string(),
             Expression::Bool(b) => b.to_string(),
             Expression::Null => "null".into(),
@@ -139,27 +189,81 @@ impl Display for Expression {
                 let mut starter = String::from("{\n");
 
                 for (key, value) in members {
-                    starter.push_str(&format!("\"{}\": {},\n", key, value));
+                    starter.push_str(&format!("{}: {},\n", quote(key), value));
                 }
 
                 starter.push_str("\n}");
                 starter
             },
-            Expression::Index(target, index) => format!("{}[{}]", *target, *index),
-            Expression::Dot(target, index) => format!("{}.{}", *target, *index),
+            Expression::Index(target, index) => format!("{}[{}]", target.target(), *index),
+            Expression::Dot(target, index) => format!("{}.{}", target.target(), *index),
             Expression::Identifier(i) => i.to_string(),
-            Expression::Infix(left, op, right) => format!("{} {} {}", *left, op, *right),
-            Expression::Prefix(op, right) => format!("{} {}", op, *right),
-            Expression::Call(callable, parameters) => format!("{}({})", *callable, parameters.iter().map(|p| p.to_string()).collect::<Vec<String>>().join(", ")),
+            Expression::Infix(left, op, right) => {
+                // Operators we don't know the precedence of get both of their operands wrapped.
+                let (lhs, rhs) = match precedence::infix(op) {
+                    Some((precedence, associativity)) => {
+                        let (mut lhs, rhs) = match associativity {
+                            Associativity::Left => (precedence, precedence + 1),
+                            Associativity::Right => (precedence + 1, precedence),
+                        };
+
+                        // JavaScript refuses to parse a unary operation on the left of `**`, e.g. `-2 ** 2`.
+                        if op == "**" {
+                            lhs = lhs.max(precedence::PREFIX + 1);
+                        }
+
+                        (lhs, rhs)
+                    },
+                    None => (precedence::PRIMARY, precedence::PRIMARY),
+                };
+
+                format!("{} {} {}", left.operand(lhs), op, right.operand(rhs))
+            },
+            Expression::Prefix(op, right) => format!("{} {}", op, right.operand(precedence::PREFIX)),
+            Expression::Call(callable, parameters) => format!("{}({})", callable.target(), parameters.iter().map(|p| p.to_string()).collect::<Vec<String>>().join(", ")),
             Expression::Closure(parameters, body) => format!("({}) => {{\n{}\n}}",
                 parameters.iter().map(|p| p.to_string()).collect::<Vec<String>>().join(", "),
                 body
             ),
-            _ => unimplemented!()
         })
     }
 }
 
+/// The mappings of `expressions`, which are written one after the other from `line` on, with `gap` more lines
+/// after each of them.
+fn nested<'a>(expressions: impl Iterator<Item = &'a Expression>, mut line: usize, gap: usize) -> Vec<Mapping> {
+    let mut mappings = Vec::new();
+
+    for expression in expressions {
+        mappings.extend(expression.mappings().into_iter().map(|m| Mapping { generated_line: line + m.generated_line, ..m }));
+
+        line += expression.to_string().matches('\n').count() + gap;
+    }
+
+    mappings
+}
+
+/// Write `s` as a JavaScript string literal, escaping any characters that can't appear in one as they are.
+pub(crate) fn quote(s: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\u{2028}' | '\u{2029}' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,6 +271,7 @@ mod tests {
     #[test]
     fn strings() {
         assert_eq!(r##""Hello!""##, Expression::from("Hello!").to_string().as_str());
+        assert_eq!(r##""say \"hi\"\n\ta\\b\u0000""##, Expression::from("say \"hi\"\n\ta\\b\0").to_string().as_str());
     }
 
     #[test]
@@ -228,4 +333,89 @@ mod tests {
             ])
         ).to_string().as_str());
     }
+
+    #[test]
+    fn precedence() {
+        let (one, two, three) = (Expression::from(1), Expression::from(2), Expression::from(3));
+
+        assert_eq!("(1 + 2) * 3", Expression::infix(Expression::infix(one.clone(), "+", two.clone()), "*", three.clone()).to_string());
+        assert_eq!("1 + 2 * 3", Expression::infix(one.clone(), "+", Expression::infix(two.clone(), "*", three.clone())).to_string());
+        assert_eq!("1 - 2 - 3", Expression::infix(Expression::infix(one.clone(), "-", two.clone()), "-", three.clone()).to_string());
+        assert_eq!("1 - (2 - 3)", Expression::infix(one.clone(), "-", Expression::infix(two.clone(), "-", three.clone())).to_string());
+        assert_eq!("(1 ** 2) ** 3", Expression::infix(Expression::infix(one.clone(), "**", two.clone()), "**", three.clone()).to_string());
+        assert_eq!("1 ** 2 ** 3", Expression::infix(one.clone(), "**", Expression::infix(two.clone(), "**", three.clone())).to_string());
+        assert_eq!("(- 1) ** 2", Expression::infix(Expression::Prefix("-".into(), one.clone().into()), "**", two.clone()).to_string());
+        assert_eq!("(-1) ** 2", Expression::infix(Expression::from(-1), "**", two.clone()).to_string());
+        assert_eq!("a = b = 1", Expression::infix(Expression::identifier("a"), "=", Expression::infix(Expression::identifier("b"), "=", one.clone())).to_string());
+        assert_eq!("! (a && b)", Expression::Prefix("!".into(), Expression::infix(Expression::identifier("a"), "&&", Expression::identifier("b")).into()).to_string());
+        assert_eq!("- -1", Expression::Prefix("-".into(), Expression::from(-1).into()).to_string());
+    }
+
+    #[test]
+    fn postfix_precedence() {
+        let sum = Expression::infix(Expression::from(1), "+", Expression::from(2));
+
+        assert_eq!("(1 + 2).toString()", Expression::from((Expression::dot(sum.clone(), Expression::identifier("toString")), vec![])).to_string());
+        assert_eq!("(1 + 2)[0]", Expression::index(sum.clone(), Expression::from(0)).to_string());
+        assert_eq!("foo[1 + 2]", Expression::index(Expression::identifier("foo"), sum.clone()).to_string());
+        assert_eq!("foo(1 + 2)", Expression::from((Expression::identifier("foo"), vec![sum])).to_string());
+        assert_eq!("(1).toString", Expression::dot(Expression::from(1), Expression::identifier("toString")).to_string());
+        assert_eq!("1.5.toString", Expression::dot(Expression::from(1.5), Expression::identifier("toString")).to_string());
+        assert_eq!("a.b.c", Expression::dot(Expression::dot(Expression::identifier("a"), Expression::identifier("b")), Expression::identifier("c")).to_string());
+    }
+
+    #[test]
+    fn closure_precedence() {
+        let mut body = Builder::new();
+        body.return_(Some(Expression::from(1)));
+
+        let closure = Expression::closure(vec![], body);
+
+        assert_eq!("(() => {\nreturn 1;\n})()", Expression::from((closure.clone(), vec![])).to_string());
+        assert_eq!("f = () => {\nreturn 1;\n}", Expression::infix(Expression::identifier("f"), "=", closure.clone()).to_string());
+        assert_eq!("(() => {\nreturn 1;\n}) || 2", Expression::infix(closure.clone(), "||", Expression::from(2)).to_string());
+        assert_eq!("2 || (() => {\nreturn 1;\n})", Expression::infix(Expression::from(2), "||", closure).to_string());
+    }
+
+    #[test]
+    fn precedence_round_trips() {
+        const INFIXES: [&str; 14] = ["+", "-", "*", "/", "%", "**", "<", ">", "<=", ">=", "===", "!==", "&&", "||"];
+        const PREFIXES: [&str; 2] = ["-", "!"];
+
+        let (a, b, c) = (Expression::from(7), Expression::from(3), Expression::from(2));
+        let mut expressions = Vec::new();
+
+        for outer in INFIXES {
+            for inner in INFIXES {
+                expressions.push(Expression::infix(Expression::infix(a.clone(), inner, b.clone()), outer, c.clone()));
+                expressions.push(Expression::infix(a.clone(), outer, Expression::infix(b.clone(), inner, c.clone())));
+            }
+
+            for prefix in PREFIXES {
+                expressions.push(Expression::Prefix(prefix.into(), Expression::infix(a.clone(), outer, b.clone()).into()));
+                expressions.push(Expression::infix(Expression::Prefix(prefix.into(), a.clone().into()), outer, b.clone()));
+                expressions.push(Expression::infix(a.clone(), outer, Expression::Prefix(prefix.into(), b.clone().into())));
+            }
+        }
+
+        let runtime = rquickjs::Runtime::new().unwrap();
+        let context = rquickjs::Context::full(&runtime).unwrap();
+
+        context.with(|ctx| {
+            let eval = |source: String| ctx.eval::<String, _>(format!("String({})", source)).unwrap_or_else(|e| panic!("{}: {:?}", source, e));
+
+            for expression in expressions {
+                assert_eq!(eval(explicit(&expression)), eval(expression.to_string()), "{}", expression);
+            }
+        });
+    }
+
+    /// Render an expression with every operation wrapped in parentheses, regardless of precedence.
+    fn explicit(expression: &Expression) -> String {
+        match expression {
+            Expression::Infix(left, op, right) => format!("({} {} {})", explicit(left), op, explicit(right)),
+            Expression::Prefix(op, right) => format!("({} {})", op, explicit(right)),
+            _ => format!("({})", expression),
+        }
+    }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use crate::{Expression, Builder};
+use crate::{Expression, Builder, Mapping};
 use std::fmt::{Display, Formatter, Result};
 
 #[derive(Debug)]
@@ -33,6 +33,13 @@ impl Function {
     }
 }
 
+impl Function {
+    /// The mappings of the body, which starts on the line after the signature.
+    pub(crate) fn mappings(&self) -> Vec<Mapping> {
+        self.body.mappings_from(1)
+    }
+}
+
 impl Display for Function {
     fn fmt(&self, f: &mut Formatter) -> Result {
         write!(f, "function {}({}) {{\n{}\n}}\n\n",
@@ -1,4 +1,4 @@
-use crate::{Expression, Builder};
+use crate::{Expression, Builder, Mapping};
 use std::fmt::{Display, Formatter, Result};
 
 #[derive(Debug)]
@@ -33,6 +33,22 @@ impl IfElse {
     }
 }
 
+impl IfElse {
+    pub(crate) fn mappings(&self) -> Vec<Mapping> {
+        // The condition can contain closures, which span multiple lines.
+        let then = self.condition.to_string().matches('\n').count() + 1;
+        let mut mappings = self.condition.mappings();
+        mappings.extend(self.then.mappings_from(then));
+
+        if let Some(otherwise) = &self.otherwise {
+            // Skip past the `}` that closes `then` and the `else {` line.
+            mappings.extend(otherwise.mappings_from(then + self.then.lines() + 2));
+        }
+
+        mappings
+    }
+}
+
 impl Display for IfElse {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(f, "if ({}) {{\n{}\n}}{}\n",
@@ -0,0 +1,53 @@
+use crate::expression::quote;
+use std::fmt::{Display, Formatter, Result};
+
+/// An `import` statement. Without any names or a namespace, it imports the module only for its side effects.
+#[derive(Debug)]
+pub struct Import {
+    module: String,
+    names: Vec<(String, Option<String>)>,
+    namespace: Option<String>,
+}
+
+impl Import {
+    pub fn new(module: String) -> Self {
+        Self {
+            module,
+            names: Vec::new(),
+            namespace: None,
+        }
+    }
+
+    /// Import the export `name`, as `alias` if there is one.
+    pub fn name(&mut self, name: String, alias: Option<String>) -> &mut Self {
+        self.names.push((name, alias));
+        self
+    }
+
+    /// Import every export of the module as the members of `namespace`, e.g. `import * as http from "http";`.
+    pub fn namespace(&mut self, namespace: String) -> &mut Self {
+        self.namespace = Some(namespace);
+        self
+    }
+}
+
+impl Display for Import {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let module = quote(&self.module);
+
+        if let Some(namespace) = &self.namespace {
+            return writeln!(f, "import * as {} from {};", namespace, module);
+        }
+
+        if self.names.is_empty() {
+            return writeln!(f, "import {};", module);
+        }
+
+        let names = self.names.iter().map(|(name, alias)| match alias {
+            Some(alias) => format!("{} as {}", name, alias),
+            None => name.clone(),
+        }).collect::<Vec<String>>();
+
+        writeln!(f, "import {{ {} }} from {};", names.join(", "), module)
+    }
+}
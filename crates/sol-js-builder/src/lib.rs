@@ -4,10 +4,17 @@ mod expression;
 mod function;
 mod if_else;
 mod r#while;
+mod try_catch;
+mod import;
+mod precedence;
+mod source_map;
 
 pub use var::Var;
 pub use expression::Expression;
 pub use builder::Builder;
 pub use function::Function;
 pub use if_else::IfElse;
-pub use r#while::While;
\ No newline at end of file
+pub use r#while::While;
+pub use try_catch::TryCatch;
+pub use import::Import;
+pub use source_map::{SourceMap, Mapping};
\ No newline at end of file
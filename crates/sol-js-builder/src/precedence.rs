@@ -0,0 +1,39 @@
+/// Binding strength of a JavaScript expression, ordered from loosest to tightest.
+///
+/// The levels follow the operator precedence table in the ECMAScript specification. Only the
+/// relative order matters, so gaps are left where the builder has no matching construct.
+pub(crate) type Precedence = u8;
+
+pub(crate) const ASSIGNMENT: Precedence = 2;
+pub(crate) const PREFIX: Precedence = 14;
+pub(crate) const POSTFIX: Precedence = 17;
+pub(crate) const PRIMARY: Precedence = 18;
+
+/// Describes which side of an operator groups first when the same operator is chained.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum Associativity {
+    Left,
+    Right,
+}
+
+/// Returns the precedence and associativity of a binary operator, or `None` if the builder
+/// does not know about the operator.
+pub(crate) fn infix(op: &str) -> Option<(Precedence, Associativity)> {
+    use Associativity::*;
+
+    Some(match op {
+        "=" | "+=" | "-=" | "*=" | "/=" | "%=" | "**=" | "<<=" | ">>=" | ">>>=" | "&=" | "^=" | "|=" | "&&=" | "||=" | "??=" => (ASSIGNMENT, Right),
+        "||" => (3, Left),
+        "&&" => (4, Left),
+        "|" => (5, Left),
+        "^" => (6, Left),
+        "&" => (7, Left),
+        "==" | "!=" | "===" | "!==" => (8, Left),
+        "<" | ">" | "<=" | ">=" | "in" | "instanceof" => (9, Left),
+        "<<" | ">>" | ">>>" => (10, Left),
+        "+" | "-" => (11, Left),
+        "*" | "/" | "%" => (12, Left),
+        "**" => (13, Right),
+        _ => return None
+    })
+}
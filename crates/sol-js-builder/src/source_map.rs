@@ -0,0 +1,311 @@
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Links a position in the generated JavaScript to a position in the original source file.
+///
+/// All lines and columns start at 0, as they do in the Source Map format. Generated columns are counted
+/// in UTF-16 code units, since that is how JavaScript engines report them.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Mapping {
+    pub generated_line: usize,
+    pub generated_column: usize,
+    pub source_line: usize,
+    pub source_column: usize,
+}
+
+/// A version 3 source map for a single source file.
+///
+/// Use `Builder::source_map` to create one for the code that a `Builder` has generated, and `to_json` to
+/// serialize it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SourceMap {
+    pub file: Option<String>,
+    pub source: String,
+    pub mappings: Vec<Mapping>,
+}
+
+impl SourceMap {
+    pub fn new(source: impl Into<String>, mut mappings: Vec<Mapping>) -> Self {
+        mappings.sort_by_key(|m| (m.generated_line, m.generated_column));
+
+        Self {
+            file: None,
+            source: source.into(),
+            mappings,
+        }
+    }
+
+    /// Set the name of the generated file that the map belongs to.
+    pub fn file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Find the mapping that covers a position in the generated code.
+    ///
+    /// This is the closest mapping at or before the position, so code that has no mapping of its own (like
+    /// a closing brace) resolves to the statement it belongs to.
+    pub fn lookup(&self, line: usize, column: usize) -> Option<&Mapping> {
+        self.mappings.iter()
+            .take_while(|m| (m.generated_line, m.generated_column) <= (line, column))
+            .last()
+    }
+
+    /// Encode the `mappings` field, a `;` separated list of lines which each hold `,` separated segments.
+    fn encode_mappings(&self) -> String {
+        let mut output = String::new();
+        let mut line = 0;
+        let mut previous_column = 0;
+        let mut previous_source_line = 0;
+        let mut previous_source_column = 0;
+
+        for mapping in self.mappings.iter() {
+            if mapping.generated_line == line && ! output.is_empty() && ! output.ends_with(';') {
+                output.push(',');
+            }
+
+            while line < mapping.generated_line {
+                output.push(';');
+                line += 1;
+                previous_column = 0;
+            }
+
+            vlq(&mut output, mapping.generated_column as i64 - previous_column as i64);
+            // Every mapping points into the only source file, at index 0.
+            vlq(&mut output, 0);
+            vlq(&mut output, mapping.source_line as i64 - previous_source_line as i64);
+            vlq(&mut output, mapping.source_column as i64 - previous_source_column as i64);
+
+            previous_column = mapping.generated_column;
+            previous_source_line = mapping.source_line;
+            previous_source_column = mapping.source_column;
+        }
+
+        output
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(r#"{{"version":3,{}"sources":[{}],"names":[],"mappings":"{}"}}"#,
+            match &self.file {
+                Some(file) => format!(r#""file":{},"#, json_string(file)),
+                None => String::new(),
+            },
+            json_string(&self.source),
+            self.encode_mappings(),
+        )
+    }
+}
+
+/// Append `value` as a Base64 VLQ, where the lowest bit of the first digit holds the sign.
+fn vlq(output: &mut String, value: i64) {
+    let mut value = if value < 0 { ((-value) << 1) | 1 } else { value << 1 };
+
+    loop {
+        let mut digit = value & 0b11111;
+        value >>= 5;
+
+        if value > 0 {
+            digit |= 0b100000;
+        }
+
+        output.push(BASE64[digit as usize] as char);
+
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut output = String::from("\"");
+
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+
+    output.push('"');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Builder, Expression, Function, IfElse, TryCatch, Var, While};
+
+    fn mapping(generated_line: usize, generated_column: usize, source_line: usize, source_column: usize) -> Mapping {
+        Mapping { generated_line, generated_column, source_line, source_column }
+    }
+
+    #[test]
+    fn vlqs() {
+        let encode = |value| {
+            let mut output = String::new();
+            vlq(&mut output, value);
+            output
+        };
+
+        assert_eq!(encode(0), "A");
+        assert_eq!(encode(1), "C");
+        assert_eq!(encode(-1), "D");
+        assert_eq!(encode(15), "e");
+        assert_eq!(encode(16), "gB");
+        assert_eq!(encode(-17), "jB");
+        assert_eq!(encode(1000), "w+B");
+    }
+
+    #[test]
+    fn json() {
+        let map = SourceMap::new("main.sol", vec![
+            mapping(0, 0, 0, 0),
+            mapping(2, 0, 1, 4),
+            mapping(2, 10, 3, 0),
+            mapping(3, 0, 2, 2),
+        ]).file("main.js");
+
+        assert_eq!(map.to_json(), r#"{"version":3,"file":"main.js","sources":["main.sol"],"names":[],"mappings":"AAAA;;AACI,UAEJ;AADE"}"#);
+        assert_eq!(SourceMap::new("a \"b\".sol", vec![]).to_json(), r#"{"version":3,"sources":["a \"b\".sol"],"names":[],"mappings":""}"#);
+    }
+
+    #[test]
+    fn lookups() {
+        let map = SourceMap::new("main.sol", vec![
+            mapping(3, 0, 5, 0),
+            mapping(0, 0, 0, 0),
+            mapping(1, 0, 2, 4),
+        ]);
+
+        assert_eq!(map.lookup(0, 5), Some(&mapping(0, 0, 0, 0)));
+        assert_eq!(map.lookup(1, 0), Some(&mapping(1, 0, 2, 4)));
+        assert_eq!(map.lookup(2, 0), Some(&mapping(1, 0, 2, 4)));
+        assert_eq!(map.lookup(3, 1), Some(&mapping(3, 0, 5, 0)));
+
+        assert_eq!(SourceMap::new("main.sol", vec![mapping(1, 0, 0, 0)]).lookup(0, 0), None);
+    }
+
+    #[test]
+    fn nested_builders() {
+        let mut then = Builder::new();
+        then.map(3, 12).break_();
+
+        let mut otherwise = Builder::new();
+        otherwise.map(5, 12).continue_();
+
+        let mut if_ = IfElse::new(true.into());
+        if_.then(then).otherwise(otherwise);
+
+        let mut condition = Builder::new();
+        condition.return_(Some(true.into()));
+
+        let mut body = Builder::new();
+        body.map(2, 8).conditional(if_);
+
+        let mut while_ = While::new(Expression::Call(Box::new(Expression::closure(vec![], condition)), vec![]));
+        while_.then(body);
+
+        let mut function = Builder::new();
+        function.map(1, 4).expression(1.0.into()).map(1, 10).while_loop(while_);
+
+        let mut f = Function::new();
+        f.id("f".to_owned()).body(function);
+
+        let mut builder = Builder::new();
+        builder.map(0, 0).function(f);
+
+        let source = builder.source();
+        let lines = source.lines().collect::<Vec<&str>>();
+        let map = builder.source_map("main.sol");
+
+        assert_eq!(map.mappings.iter().map(|m| {
+            (m.source_line, m.source_column, &lines[m.generated_line][m.generated_column..])
+        }).collect::<Vec<_>>(), vec![
+            (0, 0, "function f() {"),
+            (1, 4, "1;"),
+            (1, 10, "while ((() => {"),
+            (2, 8, "if (true) {"),
+            (3, 12, "break;"),
+            (5, 12, "continue;"),
+        ]);
+    }
+
+    #[test]
+    fn closures() {
+        let mut body = Builder::new();
+        body.map(1, 4).expression(1.0.into()).map(2, 4).return_(Some(2.0.into()));
+
+        let closure = Expression::closure(vec![], body.clone());
+        let call = Expression::Call(Box::new(Expression::identifier("f")), vec![Expression::from(0), Expression::closure(vec![], body)]);
+
+        let mut builder = Builder::new();
+        builder.map(0, 0).var({
+            let mut var = Var::new();
+            var.as_let().id("a".to_owned()).value(closure);
+            var
+        });
+        builder.map(4, 0).expression(call);
+
+        let source = builder.source();
+        let lines = source.lines().collect::<Vec<&str>>();
+
+        assert_eq!(builder.source_map("main.sol").mappings.iter().map(|m| (m.source_line, lines[m.generated_line])).collect::<Vec<_>>(), vec![
+            (0, "let a = () => {"),
+            (1, "1;"),
+            (2, "return 2;"),
+            (4, "f(0, () => {"),
+            (1, "1;"),
+            (2, "return 2;"),
+        ]);
+    }
+
+    #[test]
+    fn positions() {
+        let mut body = Builder::new();
+        body.map(2, 4).return_(Some(1.0.into()));
+
+        let mut f = Function::new();
+        f.id("f".to_owned()).body(body);
+
+        let mut declaration = Builder::new();
+        declaration.map(1, 0).function(f);
+
+        let mut builder = Builder::new();
+        builder.map(0, 0).expression("😀".into()).export(declaration).map(3, 0).expression(2.0.into());
+
+        assert_eq!(builder.source(), "\"😀\";export function f() {\nreturn 1;\n}\n\n2;");
+        // Columns count UTF-16 code units, so the emoji takes up two.
+        assert_eq!(builder.source_map("main.sol").mappings, vec![
+            mapping(0, 0, 0, 0),
+            mapping(0, 12, 1, 0),
+            mapping(1, 0, 2, 4),
+            mapping(4, 0, 3, 0),
+        ]);
+    }
+
+    #[test]
+    fn try_catch() {
+        let mut body = Builder::new();
+        body.map(0, 0).expression(1.0.into()).map(1, 0).expression(2.0.into());
+
+        let mut handler = Builder::new();
+        handler.map(2, 0).expression(3.0.into());
+
+        let mut try_catch = TryCatch::new(body);
+        try_catch.catch("error".to_owned(), handler);
+
+        let mut builder = Builder::new();
+        builder.try_catch(try_catch);
+
+        assert_eq!(builder.source(), "try {\n1;\n2;\n} catch (error) {\n3;\n}\n");
+        assert_eq!(builder.source_map("main.sol").mappings.iter().map(|m| (m.generated_line, m.source_line)).collect::<Vec<_>>(), vec![
+            (1, 0),
+            (2, 1),
+            (4, 2),
+        ]);
+    }
+}
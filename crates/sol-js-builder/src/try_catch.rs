@@ -0,0 +1,40 @@
+use crate::{Builder, Mapping};
+use std::fmt::{Display, Formatter, Result};
+
+#[derive(Debug)]
+pub struct TryCatch {
+    body: Builder,
+    binding: String,
+    handler: Builder,
+}
+
+impl TryCatch {
+    pub fn new(body: Builder) -> Self {
+        Self {
+            body,
+            binding: String::from("e"),
+            handler: Builder::new(),
+        }
+    }
+
+    pub fn catch(&mut self, binding: String, handler: Builder) -> &mut Self {
+        self.binding = binding;
+        self.handler = handler;
+        self
+    }
+
+    pub(crate) fn mappings(&self) -> Vec<Mapping> {
+        let mut mappings = self.body.mappings_from(1);
+
+        // Skip past the `}` that closes the body and the `catch` line.
+        mappings.extend(self.handler.mappings_from(self.body.lines() + 3));
+
+        mappings
+    }
+}
+
+impl Display for TryCatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "try {{\n{}\n}} catch ({}) {{\n{}\n}}\n", self.body, self.binding, self.handler)
+    }
+}
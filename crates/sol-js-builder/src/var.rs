@@ -1,4 +1,4 @@
-use crate::Expression;
+use crate::{Expression, Mapping};
 use std::fmt::{Display, Formatter, Result};
 
 #[derive(Debug)]
@@ -51,6 +51,13 @@ impl Var {
     }
 }
 
+impl Var {
+    /// The mappings of the closures in the value, which starts on the first line.
+    pub(crate) fn mappings(&self) -> Vec<Mapping> {
+        self.value.as_ref().map(|value| value.mappings()).unwrap_or_default()
+    }
+}
+
 impl Display for Var {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(f, "{} {}{};", self.keyword(), self.id, if let Some(expression) = &self.value {
@@ -1,4 +1,4 @@
-use crate::{Expression, Builder};
+use crate::{Expression, Builder, Mapping};
 use std::fmt::{Display, Formatter, Result};
 
 #[derive(Debug)]
@@ -21,6 +21,16 @@ impl While {
     }
 }
 
+impl While {
+    pub(crate) fn mappings(&self) -> Vec<Mapping> {
+        // The condition can contain closures, which span multiple lines.
+        let mut mappings = self.condition.mappings();
+        mappings.extend(self.then.mappings_from(self.condition.to_string().matches('\n').count() + 1));
+
+        mappings
+    }
+}
+
 impl Display for While {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(f, "while ({}) {{\n{}\n}}", self.condition, self.then)
@@ -0,0 +1,146 @@
+use crate::{ParserError, ParserErrorType, LexError, LexErrorType, Span};
+
+/// A problem in a piece of Sol source code, ready to be shown to the user.
+///
+/// `Diagnostic` holds the message along with the `span` of source code it refers to, plus any notes that help the user fix the problem. Use `render` to turn it into a snippet of the
+/// offending source code with the span underlined.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Render the diagnostic against the `source` it was produced from.
+    ///
+    /// ```text
+    /// error: expected `)` but found identifier `x`
+    ///  --> main.sol:1:12
+    ///   |
+    /// 1 | let b = (a x)
+    ///   |            ^
+    /// ```
+    pub fn render(&self, source: &str, file: &str) -> String {
+        let line = self.span.line;
+        let gutter = " ".repeat(line.to_string().len());
+        let mut output = format!("error: {}\n{}--> {}:{}:{}\n", self.message, gutter, file, line, self.span.column);
+
+        if let Some(text) = source.lines().nth(line.saturating_sub(1)) {
+            let start = self.span.column.saturating_sub(1);
+
+            // Spans can cover several lines, only the part on the first line is underlined.
+            let width = source.get(self.span.start..self.span.end)
+                .map(|s| s.chars().take_while(|c| *c != '\n').count())
+                .unwrap_or(0)
+                .max(1);
+
+            // Keep any tabs in front of the span so that the carets line up with the source line.
+            let indent = text.chars().take(start).map(|c| if c == '\t' { '\t' } else { ' ' }).collect::<String>();
+
+            output.push_str(&format!("{} |\n", gutter));
+            output.push_str(&format!("{} | {}\n", line, text));
+            output.push_str(&format!("{} | {}{}\n", gutter, indent, "^".repeat(width)));
+        }
+
+        for note in self.notes.iter() {
+            output.push_str(&format!("{} = help: {}\n", gutter, note));
+        }
+
+        output
+    }
+}
+
+impl From<&LexError> for Diagnostic {
+    fn from(error: &LexError) -> Self {
+        let diagnostic = Diagnostic::new(error.err.to_string(), error.span);
+
+        match error.err {
+            LexErrorType::UnterminatedString => diagnostic.note("add a `\"` to the end of the string"),
+            LexErrorType::UnterminatedComment => diagnostic.note("every `/*` needs a matching `*/`, including nested comments"),
+            LexErrorType::InvalidEscape(_) => diagnostic.note(r#"the supported escape sequences are `\n`, `\r`, `\t`, `\0`, `\"` and `\\`"#),
+            _ => diagnostic,
+        }
+    }
+}
+
+impl From<&ParserError> for Diagnostic {
+    fn from(error: &ParserError) -> Self {
+        if let ParserErrorType::Lex(err) = &error.err {
+            return Diagnostic::from(&LexError { span: error.span, err: err.clone() });
+        }
+
+        let diagnostic = Diagnostic::new(error.err.to_string(), error.span);
+
+        match error.err {
+            ParserErrorType::InvalidBreakableScope | ParserErrorType::InvalidContinuableScope => diagnostic.note("loops are written with `while condition { ... }`"),
+            ParserErrorType::NestedFunctionDefinition => diagnostic.note("assign a closure instead, e.g. `let name = fn () { ... }`"),
+            _ => diagnostic,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn renders_source_snippets() {
+        let source = "let a = 1\nlet b = (a x)";
+        let errors = parse(source).unwrap_err();
+
+        assert_eq!(Diagnostic::from(&errors[0]).render(source, "main.sol"), [
+            "error: expected `)` but found identifier `x`",
+            " --> main.sol:2:12",
+            "  |",
+            "2 | let b = (a x)",
+            "  |            ^",
+            "",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn underlines_the_whole_span() {
+        let source = "let a = 1.2.3";
+        let errors = parse(source).unwrap_err();
+
+        assert_eq!(Diagnostic::from(&errors[0]).render(source, "main.sol"), [
+            "error: invalid number literal `1.2.3`",
+            " --> main.sol:1:9",
+            "  |",
+            "1 | let a = 1.2.3",
+            "  |         ^^^^^",
+            "",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn renders_help_notes() {
+        let source = "fn a() {\n\tbreak\n}";
+        let errors = parse(source).unwrap_err();
+
+        assert_eq!(Diagnostic::from(&errors[0]).render(source, "main.sol"), [
+            "error: `break` outside of a loop",
+            " --> main.sol:2:2",
+            "  |",
+            "2 | \tbreak",
+            "  | \t^^^^^",
+            "  = help: loops are written with `while condition { ... }`",
+            "",
+        ].join("\n"));
+    }
+}
\ No newline at end of file
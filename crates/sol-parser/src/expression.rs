@@ -1,5 +1,5 @@
 use crate::TokenKind;
-use crate::{Parameter, Statement};
+use crate::{Node, Parameter, Statement};
 use std::collections::HashMap;
 
 #[derive(Debug, PartialEq)]
@@ -7,21 +7,22 @@ pub enum Expression {
     String(String),
     Number(f64),
     Bool(bool),
-    Array(Vec<Expression>),
-    Map(HashMap<String, Expression>),
+    Array(Vec<Node<Expression>>),
+    Map(HashMap<String, Node<Expression>>),
     Identifier(String),
-    Prefix(Op, Box<Expression>),
-    Infix(Box<Expression>, Op, Box<Expression>),
-    Call(Box<Expression>, Vec<Expression>),
-    Assign(Box<Expression>, Box<Expression>),
-    Index(Box<Expression>, Option<Box<Expression>>),
-    Dot(Box<Expression>, Box<Expression>),
-    Closure(Vec<Parameter>, Vec<Statement>),
+    Prefix(Op, Box<Node<Expression>>),
+    Infix(Box<Node<Expression>>, Op, Box<Node<Expression>>),
+    Call(Box<Node<Expression>>, Vec<Node<Expression>>),
+    Assign(Box<Node<Expression>>, Box<Node<Expression>>),
+    Index(Box<Node<Expression>>, Option<Box<Node<Expression>>>),
+    Dot(Box<Node<Expression>>, Box<Node<Expression>>),
+    Closure(Vec<Parameter>, Vec<Node<Statement>>),
 }
 
 impl Expression {
-    pub fn boxed(self) -> Box<Self> {
-        Box::new(self)
+    /// Wrap the expression in a boxed `Node` with an empty span.
+    pub fn boxed(self) -> Box<Node<Self>> {
+        Node::from(self).boxed()
     }
 }
 
@@ -32,6 +33,7 @@ pub enum Op {
     Subtract,
     Multiply,
     Divide,
+    Power,
     GreaterThan,
     LessThan,
     GreaterThanEquals,
@@ -49,62 +51,39 @@ pub enum Op {
     Mod,
 }
 
-impl From<TokenKind> for Op {
-    fn from(kind: TokenKind) -> Self {
-        match kind {
-            TokenKind::Plus => Op::Add,
-            TokenKind::Minus => Op::Subtract,
-            TokenKind::Asterisk => Op::Multiply,
-            TokenKind::Slash => Op::Divide,
-            TokenKind::GreaterThan => Self::GreaterThan,
-            TokenKind::LessThan => Self::LessThan,
-            TokenKind::GreaterThanEquals => Self::GreaterThanEquals,
-            TokenKind::LessThanEquals => Self::LessThanEquals,
-            TokenKind::EqualsEquals => Self::Equals,
-            TokenKind::NotEquals => Self::NotEquals,
-            TokenKind::Equals => Self::Assign,
-            TokenKind::Not => Self::Not,
-            TokenKind::And => Self::And,
-            TokenKind::Or => Self::Or,
-            TokenKind::Percent => Self::Mod,
-            TokenKind::PlusEquals => Self::AddAssign,
-            TokenKind::MinusEquals => Self::SubtractAssign,
-            TokenKind::AsteriskEquals => Self::MultiplyAssign,
-            TokenKind::SlashEquals => Self::DivideAssign,
-            _ => todo!()
-        }
-    }
-}
+/// Only tokens that are operators can be converted, any other token is given back as the error.
+impl TryFrom<&TokenKind> for Op {
+    type Error = TokenKind;
 
-impl From<&TokenKind> for Op {
-    fn from(kind: &TokenKind) -> Self {
+    fn try_from(kind: &TokenKind) -> Result<Self, Self::Error> {
         match kind {
-            TokenKind::Plus => Op::Add,
-            TokenKind::Minus => Op::Subtract,
-            TokenKind::Asterisk => Op::Multiply,
-            TokenKind::Slash => Op::Divide,
-            TokenKind::GreaterThan => Self::GreaterThan,
-            TokenKind::LessThan => Self::LessThan,
-            TokenKind::GreaterThanEquals => Self::GreaterThanEquals,
-            TokenKind::LessThanEquals => Self::LessThanEquals,
-            TokenKind::EqualsEquals => Self::Equals,
-            TokenKind::NotEquals => Self::NotEquals,
-            TokenKind::Equals => Self::Assign,
-            TokenKind::Not => Self::Not,
-            TokenKind::And => Self::And,
-            TokenKind::Or => Self::Or,
-            TokenKind::Percent => Self::Mod,
-            TokenKind::PlusEquals => Self::AddAssign,
-            TokenKind::MinusEquals => Self::SubtractAssign,
-            TokenKind::AsteriskEquals => Self::MultiplyAssign,
-            TokenKind::SlashEquals => Self::DivideAssign,
-            _ => todo!()
+            TokenKind::Plus => Ok(Self::Add),
+            TokenKind::Minus => Ok(Self::Subtract),
+            TokenKind::Asterisk => Ok(Self::Multiply),
+            TokenKind::DoubleAsterisk => Ok(Self::Power),
+            TokenKind::Slash => Ok(Self::Divide),
+            TokenKind::GreaterThan => Ok(Self::GreaterThan),
+            TokenKind::LessThan => Ok(Self::LessThan),
+            TokenKind::GreaterThanEquals => Ok(Self::GreaterThanEquals),
+            TokenKind::LessThanEquals => Ok(Self::LessThanEquals),
+            TokenKind::EqualsEquals => Ok(Self::Equals),
+            TokenKind::NotEquals => Ok(Self::NotEquals),
+            TokenKind::Equals => Ok(Self::Assign),
+            TokenKind::Not => Ok(Self::Not),
+            TokenKind::And => Ok(Self::And),
+            TokenKind::Or => Ok(Self::Or),
+            TokenKind::Percent => Ok(Self::Mod),
+            TokenKind::PlusEquals => Ok(Self::AddAssign),
+            TokenKind::MinusEquals => Ok(Self::SubtractAssign),
+            TokenKind::AsteriskEquals => Ok(Self::MultiplyAssign),
+            TokenKind::SlashEquals => Ok(Self::DivideAssign),
+            _ => Err(kind.clone()),
         }
     }
 }
 
 impl Op {
     pub fn math(&self) -> bool {
-        matches!(self, Self::Add | Self::Subtract | Self::Multiply | Self::Divide)
+        matches!(self, Self::Add | Self::Subtract | Self::Multiply | Self::Divide | Self::Power)
     }
 }
\ No newline at end of file
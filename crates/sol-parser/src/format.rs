@@ -0,0 +1,594 @@
+use crate::{Expression, Imports, Lexer, Node, Op, Parameter, Parser, ParserError, Statement};
+use std::ops::Range;
+
+const INDENT: &str = "    ";
+
+/// Format Sol source code in the canonical style.
+///
+/// Comments are kept, but since they aren't part of the syntax tree, a comment in the middle of an expression
+/// is moved in front of the next statement. A single blank line is kept wherever the source had one or more.
+pub fn format(source: &str) -> Result<String, Vec<ParserError>> {
+    // The parser is used directly, since the passes that `parse` runs would reorder the statements.
+    let program = Parser::new(Lexer::new(source)).parse()?;
+
+    let mut formatter = Formatter {
+        source,
+        comments: comments(source),
+        next_comment: 0,
+        output: String::new(),
+        indent: 0,
+        last: 0,
+        first: true,
+    };
+
+    formatter.statements(&program, source.len());
+
+    Ok(formatter.output)
+}
+
+/// Find the position of every comment, which are the only thing in between tokens other than whitespace.
+fn comments(source: &str) -> Vec<Range<usize>> {
+    let mut comments = Vec::new();
+    let mut end = 0;
+
+    let gap = |comments: &mut Vec<Range<usize>>, start: usize, end: usize| {
+        let mut position = start;
+
+        while position < end {
+            let rest = &source[position..end];
+
+            if rest.starts_with("//") {
+                let length = rest.find('\n').unwrap_or(rest.len());
+
+                comments.push(position..position + length);
+                position += length;
+            } else if rest.starts_with("/*") {
+                let mut depth = 0;
+                let mut length = 0;
+
+                // Block comments can be nested, so the comment only ends at the matching `*/`.
+                while length < rest.len() {
+                    if rest[length..].starts_with("/*") {
+                        depth += 1;
+                        length += 2;
+                    } else if rest[length..].starts_with("*/") {
+                        depth -= 1;
+                        length += 2;
+
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        length += rest[length..].chars().next().map_or(1, char::len_utf8);
+                    }
+                }
+
+                comments.push(position..position + length);
+                position += length;
+            } else {
+                position += rest.chars().next().map_or(1, char::len_utf8);
+            }
+        }
+    };
+
+    for token in Lexer::new(source).flatten() {
+        gap(&mut comments, end, token.span.start);
+        end = token.span.end;
+    }
+
+    gap(&mut comments, end, source.len());
+
+    comments
+}
+
+struct Formatter<'s> {
+    source: &'s str,
+    comments: Vec<Range<usize>>,
+    next_comment: usize,
+    output: String,
+    indent: usize,
+    /// The end of the last statement or comment that was written, as a position in the source.
+    last: usize,
+    /// Whether nothing has been written in the current block yet.
+    first: bool,
+}
+
+impl<'s> Formatter<'s> {
+    fn statements(&mut self, statements: &[Node<Statement>], end: usize) {
+        for statement in statements {
+            self.comments(statement.span.start);
+            self.start_line(statement.span.start);
+            self.statement(statement);
+            self.output.push('\n');
+
+            self.last = statement.span.end;
+            self.first = false;
+        }
+
+        self.comments(end);
+    }
+
+    /// Write every comment that comes before `position` in the source.
+    fn comments(&mut self, position: usize) {
+        while let Some(comment) = self.comments.get(self.next_comment).cloned() {
+            if comment.start >= position {
+                break;
+            }
+
+            let text = self.source[comment.clone()].trim_end();
+
+            // A comment at the end of a line stays there, unless it was inside of the last statement.
+            let trailing = self.source.get(self.last..comment.start).is_some_and(|gap| ! gap.contains('\n'));
+
+            if trailing && self.output.ends_with('\n') {
+                self.output.pop();
+                self.output.push(' ');
+            } else {
+                self.start_line(comment.start);
+            }
+
+            self.output.push_str(text);
+            self.output.push('\n');
+
+            self.next_comment += 1;
+            self.last = comment.end;
+            self.first = false;
+        }
+    }
+
+    /// Indent a new line for whatever starts at `position`, keeping a blank line in front of it if there was one.
+    fn start_line(&mut self, position: usize) {
+        let gap = self.source.get(self.last..position).unwrap_or_default();
+
+        if ! self.first && gap.matches('\n').count() > 1 {
+            self.output.push('\n');
+        }
+
+        self.output.push_str(&INDENT.repeat(self.indent));
+    }
+
+    /// Write the statements of a block that starts at `start` and whose closing brace is at `end`, after the
+    /// opening brace has been written.
+    fn block(&mut self, statements: &[Node<Statement>], start: usize, end: usize) {
+        let has_comments = self.comments.get(self.next_comment).is_some_and(|c| c.start < end);
+
+        if statements.is_empty() && ! has_comments {
+            self.output.push('}');
+            return;
+        }
+
+        self.output.push('\n');
+        self.indent += 1;
+        self.last = start;
+        self.first = true;
+
+        self.statements(statements, end);
+
+        self.indent -= 1;
+        self.output.push_str(&INDENT.repeat(self.indent));
+        self.output.push('}');
+    }
+
+    fn statement(&mut self, statement: &Node<Statement>) {
+        let span = statement.span;
+
+        match &statement.kind {
+            Statement::Let { identifier, r#type, initial, .. } => {
+                self.output.push_str("let ");
+                self.output.push_str(identifier);
+
+                if let Some(r#type) = r#type {
+                    self.output.push_str(": ");
+                    self.output.push_str(&r#type.0);
+                }
+
+                self.output.push_str(" = ");
+                self.expression(initial);
+            },
+            Statement::Function { identifier, parameters, return_type, body, .. } => {
+                self.output.push_str("fn ");
+                self.output.push_str(identifier);
+                self.parameters(parameters);
+
+                if let Some(return_type) = return_type {
+                    self.output.push_str(" :: ");
+                    self.output.push_str(&return_type.0);
+                }
+
+                self.output.push_str(" {");
+                self.block(body, span.start, span.end - 1);
+            },
+            Statement::If { .. } => self.if_(statement),
+            Statement::While { condition, then } => {
+                self.output.push_str("while ");
+                self.expression(condition);
+                self.output.push_str(" {");
+                self.block(then, span.start, span.end - 1);
+            },
+            Statement::Return { expression } => {
+                self.output.push_str("return ");
+                self.expression(expression);
+            },
+            Statement::Expression { expression } => self.expression(expression),
+            Statement::Use { module, imports } => {
+                // Named imports are always written without braces, which are optional.
+                let names = match imports {
+                    Imports::Named(names) => Some(names.iter().map(|import| match &import.alias {
+                        Some(alias) => format!("{} as {}", import.name, alias),
+                        None => import.name.clone(),
+                    }).collect::<Vec<String>>().join(", ")),
+                    Imports::Namespace(namespace) => Some(format!("* as {}", namespace)),
+                    Imports::Bare => None,
+                };
+
+                self.output.push_str("import ");
+
+                if let Some(names) = names {
+                    self.output.push_str(&names);
+                    self.output.push_str(" from ");
+                }
+
+                self.output.push_str(&quote(module));
+            },
+            Statement::Export { declaration: Some(declaration), .. } => {
+                self.output.push_str("export ");
+                self.statement(declaration);
+            },
+            Statement::Export { declaration: None, names } if names.is_empty() => self.output.push_str("export {}"),
+            Statement::Export { declaration: None, names } => {
+                self.output.push_str("export { ");
+                self.output.push_str(&names.join(", "));
+                self.output.push_str(" }");
+            },
+            Statement::Break => self.output.push_str("break"),
+            Statement::Continue => self.output.push_str("continue"),
+            Statement::Error => unreachable!("only valid programs are formatted"),
+        }
+    }
+
+    fn if_(&mut self, statement: &Node<Statement>) {
+        let (condition, then, otherwise) = match &statement.kind {
+            Statement::If { condition, then, otherwise } => (condition, then, otherwise),
+            _ => unreachable!(),
+        };
+
+        self.output.push_str("if ");
+        self.expression(condition);
+        self.output.push_str(" {");
+
+        // The position of the closing brace of the `then` block isn't known when there is an `else` block, so
+        // comments in between the two end up at the end of the `then` block.
+        let then_end = otherwise.first().map_or(statement.span.end - 1, |s| s.span.start);
+        self.block(then, statement.span.start, then_end);
+
+        if otherwise.is_empty() {
+            return;
+        }
+
+        self.output.push_str(" else ");
+
+        match otherwise.as_slice() {
+            // `else if` is parsed as an `if` statement on its own in the `else` block.
+            [r#if] if matches!(r#if.kind, Statement::If { .. }) && r#if.span.end == statement.span.end => self.if_(r#if),
+            _ => {
+                self.output.push('{');
+                self.block(otherwise, then_end, statement.span.end - 1);
+            },
+        }
+    }
+
+    fn parameters(&mut self, parameters: &[Parameter]) {
+        let parameters = parameters.iter().map(|p| match &p.r#type {
+            Some(r#type) => format!("{}: {}", p.name, r#type.0),
+            None => p.name.clone(),
+        }).collect::<Vec<String>>();
+
+        self.output.push('(');
+        self.output.push_str(&parameters.join(", "));
+        self.output.push(')');
+    }
+
+    fn expression(&mut self, expression: &Node<Expression>) {
+        match &expression.kind {
+            Expression::String(s) => self.output.push_str(&quote(s)),
+            Expression::Number(n) => self.output.push_str(&n.to_string()),
+            Expression::Bool(b) => self.output.push_str(&b.to_string()),
+            Expression::Identifier(i) => self.output.push_str(i),
+            Expression::Array(items) => {
+                self.output.push('[');
+                self.items(items, self.is_multiline(expression), |f, item| f.expression(item));
+                self.output.push(']');
+            },
+            Expression::Map(members) => {
+                // The members are kept in a `HashMap`, so the spans are used to keep them in their original order.
+                let mut members = members.iter().collect::<Vec<_>>();
+                members.sort_by_key(|(_, value)| value.span.start);
+
+                self.output.push('{');
+                self.items(&members, self.is_multiline(expression), |f, (key, value)| {
+                    f.output.push_str(&quote(key));
+                    f.output.push_str(": ");
+                    f.expression(value);
+                });
+                self.output.push('}');
+            },
+            Expression::Prefix(op, rhs) => {
+                self.output.push_str(match op {
+                    Op::Not => "!",
+                    _ => "-",
+                });
+                self.operand(rhs, precedence(&rhs.kind) < PREFIX);
+            },
+            Expression::Infix(lhs, op, rhs) => {
+                let (left, right) = binding(op);
+
+                self.operand(lhs, precedence(&lhs.kind) < left);
+                self.output.push(' ');
+                self.output.push_str(operator(op));
+                self.output.push(' ');
+                self.operand(rhs, precedence(&rhs.kind) < right);
+            },
+            Expression::Assign(lhs, rhs) => {
+                self.operand(lhs, precedence(&lhs.kind) <= ASSIGN);
+                self.output.push_str(" = ");
+                // A closure can only be the last operand, since its body would take everything after it.
+                self.operand(rhs, precedence(&rhs.kind) < ASSIGN && ! matches!(rhs.kind, Expression::Closure(..)));
+            },
+            Expression::Call(callee, args) => {
+                self.operand(callee, precedence(&callee.kind) < POSTFIX);
+                self.output.push('(');
+                self.list(args);
+                self.output.push(')');
+            },
+            Expression::Index(lhs, index) => {
+                self.operand(lhs, precedence(&lhs.kind) < POSTFIX);
+                self.output.push('[');
+
+                if let Some(index) = index {
+                    self.expression(index);
+                }
+
+                self.output.push(']');
+            },
+            Expression::Dot(lhs, rhs) => {
+                self.operand(lhs, precedence(&lhs.kind) < POSTFIX);
+                self.output.push('.');
+                self.expression(rhs);
+            },
+            Expression::Closure(parameters, body) => {
+                self.output.push_str("fn ");
+                self.parameters(parameters);
+
+                match body.as_slice() {
+                    // `fn () -> x` is parsed into a return statement with the same span as the expression.
+                    [Node { kind: Statement::Return { expression: returned }, span }] if *span == returned.span => {
+                        self.output.push_str(" -> ");
+                        self.expression(returned);
+                    },
+                    _ => {
+                        self.output.push_str(" {");
+                        self.block(body, expression.span.start, expression.span.end - 1);
+                    },
+                }
+            },
+        }
+    }
+
+    fn operand(&mut self, expression: &Node<Expression>, parenthesize: bool) {
+        if parenthesize {
+            self.output.push('(');
+            self.expression(expression);
+            self.output.push(')');
+        } else {
+            self.expression(expression);
+        }
+    }
+
+    fn list(&mut self, items: &[Node<Expression>]) {
+        self.items(items, false, |f, item| f.expression(item));
+    }
+
+    /// Write the items of an array or map, either on one line or each on a line of their own.
+    fn items<T>(&mut self, items: &[T], multiline: bool, mut write: impl FnMut(&mut Self, &T)) {
+        if multiline && ! items.is_empty() {
+            self.indent += 1;
+
+            for item in items {
+                self.output.push('\n');
+                self.output.push_str(&INDENT.repeat(self.indent));
+                write(self, item);
+                self.output.push(',');
+            }
+
+            self.indent -= 1;
+            self.output.push('\n');
+            self.output.push_str(&INDENT.repeat(self.indent));
+
+            return;
+        }
+
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.output.push_str(", ");
+            }
+
+            write(self, item);
+        }
+    }
+
+    /// Whether an array or map was written over multiple lines, in which case it's kept that way.
+    fn is_multiline(&self, expression: &Node<Expression>) -> bool {
+        self.source[expression.span.start..expression.span.end].contains('\n')
+    }
+}
+
+const CLOSURE: u8 = 0;
+const ASSIGN: u8 = 1;
+const PREFIX: u8 = 17;
+const POSTFIX: u8 = 19;
+const PRIMARY: u8 = 20;
+
+/// How tightly an expression binds, matching the binding powers of the parser.
+fn precedence(expression: &Expression) -> u8 {
+    match expression {
+        Expression::Closure(..) => CLOSURE,
+        Expression::Assign(..) => ASSIGN,
+        Expression::Infix(_, op, _) => binding(op).0.min(binding(op).1),
+        Expression::Prefix(..) => PREFIX,
+        Expression::Call(..) | Expression::Index(..) | Expression::Dot(..) => POSTFIX,
+        _ => PRIMARY,
+    }
+}
+
+/// The lowest precedence that the left and right operands of `op` can have without parentheses.
+fn binding(op: &Op) -> (u8, u8) {
+    match op {
+        // `**` is right associative.
+        Op::Power => (16, 15),
+        Op::Multiply | Op::Divide | Op::Mod => (13, 14),
+        Op::Add | Op::Subtract => (11, 12),
+        Op::GreaterThan | Op::GreaterThanEquals | Op::LessThan | Op::LessThanEquals => (9, 10),
+        Op::Equals | Op::NotEquals => (7, 8),
+        Op::And => (5, 6),
+        Op::Or => (3, 4),
+        // Assignments are right associative.
+        Op::Assign | Op::AddAssign | Op::SubtractAssign | Op::MultiplyAssign | Op::DivideAssign | Op::Not => (2, ASSIGN),
+    }
+}
+
+fn operator(op: &Op) -> &'static str {
+    match op {
+        Op::Add => "+",
+        Op::Subtract => "-",
+        Op::Multiply => "*",
+        Op::Divide => "/",
+        Op::Power => "**",
+        Op::Mod => "%",
+        Op::GreaterThan => ">",
+        Op::LessThan => "<",
+        Op::GreaterThanEquals => ">=",
+        Op::LessThanEquals => "<=",
+        Op::Equals => "==",
+        Op::NotEquals => "!=",
+        Op::Assign => "=",
+        Op::AddAssign => "+=",
+        Op::SubtractAssign => "-=",
+        Op::MultiplyAssign => "*=",
+        Op::DivideAssign => "/=",
+        Op::Not => "!",
+        Op::And => "&&",
+        Op::Or => "||",
+    }
+}
+
+/// Write a string literal, escaping the characters that the lexer would otherwise read differently.
+fn quote(s: &str) -> String {
+    let mut output = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\0' => output.push_str("\\0"),
+            c => output.push(c),
+        }
+    }
+
+    output.push('"');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn assert_formats(source: &str, expected: &str) {
+        let formatted = format(source).unwrap();
+
+        assert_eq!(formatted, expected);
+        // Formatting is idempotent, and never changes what the code means.
+        assert_eq!(format(&formatted).unwrap(), formatted);
+        assert_eq!(parse(&formatted).unwrap(), parse(source).unwrap());
+    }
+
+    #[test]
+    fn statements() {
+        assert_formats(
+            "let   a:Number=1\nfn add(a,b:Number)::Number{return a+b}\nwhile a<10{a+=1\nif a==5{break}else if a==6 {continue} else {println(a)}}\nimport Uuid,Token from \"misc/uuid\"",
+            "let a: Number = 1\nfn add(a, b: Number) :: Number {\n    return a + b\n}\nwhile a < 10 {\n    a += 1\n    if a == 5 {\n        break\n    } else if a == 6 {\n        continue\n    } else {\n        println(a)\n    }\n}\nimport Uuid, Token from \"misc/uuid\"\n",
+        );
+    }
+
+    #[test]
+    fn imports() {
+        assert_formats(
+            "import   *  as http from \"web/http\"\nimport {Server as S,Token} from \"web/http\"\nimport   \"polyfill\"",
+            "import * as http from \"web/http\"\nimport Server as S, Token from \"web/http\"\nimport \"polyfill\"\n",
+        );
+    }
+
+    #[test]
+    fn exports() {
+        assert_formats(
+            "export   let a=1\nexport fn b(){}\nexport {a,b}\nexport {}",
+            "export let a = 1\nexport fn b() {}\nexport { a, b }\nexport {}\n",
+        );
+    }
+
+    #[test]
+    fn expressions() {
+        assert_formats(
+            "let a = (1 + 2) * 3 - (4 - 5) + -(a + 1) + !b\nlet b = {\"x\": [1,2 , 3], \"y\": \"a\\\"b\\n\"}\nlet c = fn(x)->x*2\nlet d = (fn () {})()\nlet e = a.b.c(d)[0][]\na = b = c",
+            "let a = (1 + 2) * 3 - (4 - 5) + -(a + 1) + !b\nlet b = {\"x\": [1, 2, 3], \"y\": \"a\\\"b\\n\"}\nlet c = fn (x) -> x * 2\nlet d = (fn () {})()\nlet e = a.b.c(d)[0][]\na = b = c\n",
+        );
+    }
+
+    #[test]
+    fn powers() {
+        assert_formats(
+            "let a = 2 ** (3 ** 2)\nlet b = (2 ** 3) ** 2\nlet c = 2 * 3 ** 2\nlet d = (2 * 3) ** 2\nlet e = -(2 ** 2)\nlet f = (-2) ** -2",
+            "let a = 2 ** 3 ** 2\nlet b = (2 ** 3) ** 2\nlet c = 2 * 3 ** 2\nlet d = (2 * 3) ** 2\nlet e = -(2 ** 2)\nlet f = -2 ** -2\n",
+        );
+    }
+
+    #[test]
+    fn multiline_literals() {
+        assert_formats(
+            "let a = [1,\n2]\nlet b = {\"x\": [\n[1, 2]], \"y\": 2\n}",
+            "let a = [\n    1,\n    2,\n]\nlet b = {\n    \"x\": [\n        [1, 2],\n    ],\n    \"y\": 2,\n}\n",
+        );
+    }
+
+    #[test]
+    fn closures() {
+        assert_formats(
+            "let f = fn (a) {\nlet b = a\nreturn fn () -> b\n}",
+            "let f = fn (a) {\n    let b = a\n    return fn () -> b\n}\n",
+        );
+    }
+
+    #[test]
+    fn blank_lines() {
+        assert_formats(
+            "let a = 1\n\n\n\nlet b = 2\nfn c() {\n\n    let d = 1\n\n    return d\n}\n",
+            "let a = 1\n\nlet b = 2\nfn c() {\n    let d = 1\n\n    return d\n}\n",
+        );
+    }
+
+    #[test]
+    fn comments() {
+        assert_formats(
+            "// The answer\nlet a = 42 // Not 41\n\n/* A /* nested */ comment */\nfn b() {\n    // Nothing yet\n}\nlet c = [\n    1, // one\n    2,\n]\n",
+            "// The answer\nlet a = 42 // Not 41\n\n/* A /* nested */ comment */\nfn b() {\n    // Nothing yet\n}\nlet c = [\n    1,\n    2,\n]\n// one\n",
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(format("let = 1").is_err());
+    }
+}
@@ -0,0 +1,389 @@
+use crate::{Expression, Import, Imports, Node, Op, Parameter, Program, Span, Statement, Token, TokenKind};
+use std::fmt::{Display, Formatter, Write};
+
+/// A JSON value, used to dump tokens and syntax trees for tools that don't understand Rust.
+///
+/// Use `{}` to format it on a single line, or `{:#}` to indent it.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Create an object from its members, which are written in the order that they are given.
+    pub fn object<'a>(members: impl IntoIterator<Item = (&'a str, Json)>) -> Self {
+        Self::Object(members.into_iter().map(|(k, v)| (k.to_owned(), v)).collect())
+    }
+
+    fn write(&self, f: &mut Formatter<'_>, indent: usize) -> std::fmt::Result {
+        let pretty = f.alternate();
+        let newline = |f: &mut Formatter<'_>, indent: usize| if pretty {
+            write!(f, "\n{}", "  ".repeat(indent))
+        } else {
+            Ok(())
+        };
+
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            // JSON has no way to write infinity or NaN.
+            Json::Number(n) if ! n.is_finite() => f.write_str("null"),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(s) => write_string(f, s),
+            Json::Array(items) if items.is_empty() => f.write_str("[]"),
+            Json::Array(items) => {
+                f.write_char('[')?;
+
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+
+                    newline(f, indent + 1)?;
+                    item.write(f, indent + 1)?;
+                }
+
+                newline(f, indent)?;
+                f.write_char(']')
+            },
+            Json::Object(members) if members.is_empty() => f.write_str("{}"),
+            Json::Object(members) => {
+                f.write_char('{')?;
+
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+
+                    newline(f, indent + 1)?;
+                    write_string(f, key)?;
+                    f.write_str(if pretty { ": " } else { ":" })?;
+                    value.write(f, indent + 1)?;
+                }
+
+                newline(f, indent)?;
+                f.write_char('}')
+            },
+        }
+    }
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+fn write_string(f: &mut Formatter<'_>, s: &str) -> std::fmt::Result {
+    f.write_char('"')?;
+
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+
+    f.write_char('"')
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_owned())
+    }
+}
+
+impl From<&String> for Json {
+    fn from(s: &String) -> Self {
+        Json::String(s.clone())
+    }
+}
+
+fn list<'a, T>(items: &'a [T]) -> Json where Json: From<&'a T> {
+    Json::Array(items.iter().map(Json::from).collect())
+}
+
+fn optional(value: Option<&String>) -> Json {
+    value.map_or(Json::Null, Json::from)
+}
+
+impl From<&Span> for Json {
+    fn from(span: &Span) -> Self {
+        Json::object([
+            ("start", Json::Number(span.start as f64)),
+            ("end", Json::Number(span.end as f64)),
+            ("line", Json::Number(span.line as f64)),
+            ("column", Json::Number(span.column as f64)),
+        ])
+    }
+}
+
+impl From<&Token> for Json {
+    fn from(token: &Token) -> Self {
+        let value = match &token.kind {
+            TokenKind::Identifier(s) | TokenKind::String(s) => Some(Json::from(s)),
+            TokenKind::Number(n) => Some(Json::Number(*n)),
+            _ => None,
+        };
+
+        let mut members = vec![("kind", Json::String(kind(&token.kind)))];
+        members.extend(value.map(|v| ("value", v)));
+        members.extend(token.doc.as_ref().map(|doc| ("doc", Json::from(doc))));
+        members.push(("span", Json::from(&token.span)));
+
+        Json::object(members)
+    }
+}
+
+impl From<&[Token]> for Json {
+    fn from(tokens: &[Token]) -> Self {
+        list(tokens)
+    }
+}
+
+/// The name of the token's kind without any value, e.g. `Identifier` for `Identifier("a")`.
+fn kind(kind: &TokenKind) -> String {
+    let name = format!("{:?}", kind);
+
+    match name.find('(') {
+        Some(i) => name[..i].to_owned(),
+        None => name,
+    }
+}
+
+/// Nodes are written as the object of their kind, with a `span` member added to it.
+fn node<'a, T>(node: &'a Node<T>) -> Json where Json: From<&'a T> {
+    match Json::from(&node.kind) {
+        Json::Object(mut members) => {
+            members.push(("span".to_owned(), <Json as From<&Span>>::from(&node.span)));
+            Json::Object(members)
+        },
+        json => json,
+    }
+}
+
+impl From<&Node<Statement>> for Json {
+    fn from(statement: &Node<Statement>) -> Self {
+        node(statement)
+    }
+}
+
+impl From<&Program> for Json {
+    fn from(program: &Program) -> Self {
+        list(program)
+    }
+}
+
+impl From<&Node<Expression>> for Json {
+    fn from(expression: &Node<Expression>) -> Self {
+        node(expression)
+    }
+}
+
+impl From<&Parameter> for Json {
+    fn from(parameter: &Parameter) -> Self {
+        Json::object([
+            ("name", Json::from(&parameter.name)),
+            ("annotation", optional(parameter.r#type.as_ref().map(|t| &t.0))),
+        ])
+    }
+}
+
+impl From<&Imports> for Json {
+    fn from(imports: &Imports) -> Self {
+        match imports {
+            Imports::Named(names) => Json::object([
+                ("type", "Named".into()),
+                ("names", list(names)),
+            ]),
+            Imports::Namespace(namespace) => Json::object([
+                ("type", "Namespace".into()),
+                ("name", namespace.into()),
+            ]),
+            Imports::Bare => Json::object([("type", "Bare".into())]),
+        }
+    }
+}
+
+impl From<&Import> for Json {
+    fn from(import: &Import) -> Self {
+        Json::object([
+            ("name", Json::from(&import.name)),
+            ("alias", optional(import.alias.as_ref())),
+        ])
+    }
+}
+
+impl From<&Op> for Json {
+    fn from(op: &Op) -> Self {
+        Json::String(format!("{:?}", op))
+    }
+}
+
+impl From<&Statement> for Json {
+    fn from(statement: &Statement) -> Self {
+        match statement {
+            Statement::Let { identifier, r#type, initial, doc } => Json::object([
+                ("type", "Let".into()),
+                ("identifier", identifier.into()),
+                ("annotation", optional(r#type.as_ref().map(|t| &t.0))),
+                ("initial", initial.into()),
+                ("doc", optional(doc.as_ref())),
+            ]),
+            Statement::Function { identifier, parameters, return_type, body, doc } => Json::object([
+                ("type", "Function".into()),
+                ("identifier", identifier.into()),
+                ("parameters", list(parameters)),
+                ("returnType", optional(return_type.as_ref().map(|t| &t.0))),
+                ("body", list(body)),
+                ("doc", optional(doc.as_ref())),
+            ]),
+            Statement::If { condition, then, otherwise } => Json::object([
+                ("type", "If".into()),
+                ("condition", condition.into()),
+                ("then", list(then)),
+                ("otherwise", list(otherwise)),
+            ]),
+            Statement::While { condition, then } => Json::object([
+                ("type", "While".into()),
+                ("condition", condition.into()),
+                ("then", list(then)),
+            ]),
+            Statement::Return { expression } => Json::object([
+                ("type", "Return".into()),
+                ("expression", expression.into()),
+            ]),
+            Statement::Expression { expression } => Json::object([
+                ("type", "Expression".into()),
+                ("expression", expression.into()),
+            ]),
+            Statement::Use { module, imports } => Json::object([
+                ("type", "Use".into()),
+                ("module", module.into()),
+                ("imports", imports.into()),
+            ]),
+            Statement::Export { declaration, names } => Json::object([
+                ("type", "Export".into()),
+                ("declaration", declaration.as_deref().map_or(Json::Null, Json::from)),
+                ("names", list(names)),
+            ]),
+            Statement::Break => Json::object([("type", "Break".into())]),
+            Statement::Continue => Json::object([("type", "Continue".into())]),
+            Statement::Error => Json::object([("type", "Error".into())]),
+        }
+    }
+}
+
+impl From<&Expression> for Json {
+    fn from(expression: &Expression) -> Self {
+        match expression {
+            Expression::String(s) => Json::object([("type", "String".into()), ("value", s.into())]),
+            Expression::Number(n) => Json::object([("type", "Number".into()), ("value", Json::Number(*n))]),
+            Expression::Bool(b) => Json::object([("type", "Bool".into()), ("value", Json::Bool(*b))]),
+            Expression::Array(items) => Json::object([("type", "Array".into()), ("items", list(items))]),
+            Expression::Map(members) => {
+                // The members are kept in a `HashMap`, so the spans are used to write them in their original order.
+                let mut members = members.iter().collect::<Vec<_>>();
+                members.sort_by_key(|(_, value)| value.span.start);
+
+                Json::object([
+                    ("type", "Map".into()),
+                    ("members", Json::Array(members.into_iter().map(|(key, value)| Json::object([
+                        ("key", key.into()),
+                        ("value", value.into()),
+                    ])).collect())),
+                ])
+            },
+            Expression::Identifier(i) => Json::object([("type", "Identifier".into()), ("name", i.into())]),
+            Expression::Prefix(op, rhs) => Json::object([
+                ("type", "Prefix".into()),
+                ("op", op.into()),
+                ("rhs", rhs.as_ref().into()),
+            ]),
+            Expression::Infix(lhs, op, rhs) => Json::object([
+                ("type", "Infix".into()),
+                ("lhs", lhs.as_ref().into()),
+                ("op", op.into()),
+                ("rhs", rhs.as_ref().into()),
+            ]),
+            Expression::Call(callee, args) => Json::object([
+                ("type", "Call".into()),
+                ("callee", callee.as_ref().into()),
+                ("args", list(args)),
+            ]),
+            Expression::Assign(target, value) => Json::object([
+                ("type", "Assign".into()),
+                ("target", target.as_ref().into()),
+                ("value", value.as_ref().into()),
+            ]),
+            Expression::Index(target, index) => Json::object([
+                ("type", "Index".into()),
+                ("target", target.as_ref().into()),
+                ("index", index.as_deref().map_or(Json::Null, Json::from)),
+            ]),
+            Expression::Dot(lhs, rhs) => Json::object([
+                ("type", "Dot".into()),
+                ("lhs", lhs.as_ref().into()),
+                ("rhs", rhs.as_ref().into()),
+            ]),
+            Expression::Closure(parameters, body) => Json::object([
+                ("type", "Closure".into()),
+                ("parameters", list(parameters)),
+                ("body", list(body)),
+            ]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, Lexer};
+
+    #[test]
+    fn values() {
+        let json = Json::object([
+            ("a", Json::Array(vec![Json::Number(1.0), Json::Number(1.5), Json::Null])),
+            ("b", Json::from("\"quoted\"\n")),
+            ("c", Json::object([])),
+            ("d", Json::Number(f64::INFINITY)),
+        ]);
+
+        assert_eq!(json.to_string(), r#"{"a":[1,1.5,null],"b":"\"quoted\"\n","c":{},"d":null}"#);
+        assert_eq!(format!("{:#}", json), "{\n  \"a\": [\n    1,\n    1.5,\n    null\n  ],\n  \"b\": \"\\\"quoted\\\"\\n\",\n  \"c\": {},\n  \"d\": null\n}");
+    }
+
+    #[test]
+    fn tokens() {
+        let tokens = Lexer::new("let a = \"b\"").map(|t| Json::from(&t.unwrap()).to_string()).collect::<Vec<String>>();
+
+        assert_eq!(tokens, vec![
+            r#"{"kind":"Let","span":{"start":0,"end":3,"line":1,"column":1}}"#,
+            r#"{"kind":"Identifier","value":"a","span":{"start":4,"end":5,"line":1,"column":5}}"#,
+            r#"{"kind":"Equals","span":{"start":6,"end":7,"line":1,"column":7}}"#,
+            r#"{"kind":"String","value":"b","span":{"start":8,"end":11,"line":1,"column":9}}"#,
+        ]);
+    }
+
+    #[test]
+    fn programs() {
+        let program = parse("f(-x)").unwrap();
+
+        assert_eq!(Json::from(&program).to_string(), concat!(
+            r#"[{"type":"Expression","expression":{"type":"Call","#,
+            r#""callee":{"type":"Identifier","name":"f","span":{"start":0,"end":1,"line":1,"column":1}},"#,
+            r#""args":[{"type":"Prefix","op":"Subtract","rhs":{"type":"Identifier","name":"x","span":{"start":3,"end":4,"line":1,"column":4}},"span":{"start":2,"end":4,"line":1,"column":3}}],"#,
+            r#""span":{"start":0,"end":5,"line":1,"column":1}},"span":{"start":0,"end":5,"line":1,"column":1}}]"#,
+        ));
+    }
+}
@@ -1,13 +1,55 @@
 use std::iter::Peekable;
 use std::str::Chars;
-use crate::{Token, TokenKind, Span};
+use std::fmt::{Display, Formatter};
+use crate::{Token, TokenKind, Span, FileId};
+
+/// An error encountered while producing `Token` instances, along with where in the source it happened.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LexError {
+    pub span: Span,
+    pub err: LexErrorType,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum LexErrorType {
+    UnterminatedString,
+    UnterminatedComment,
+    InvalidNumber(String),
+    UnknownCharacter(char),
+    InvalidEscape(char),
+}
+
+impl Display for LexErrorType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LexErrorType::UnterminatedString => write!(f, "unterminated string literal"),
+            LexErrorType::UnterminatedComment => write!(f, "unterminated block comment"),
+            LexErrorType::InvalidNumber(n) => write!(f, "invalid number literal `{}`", n),
+            LexErrorType::UnknownCharacter(c) => write!(f, "unknown character `{}`", c),
+            LexErrorType::InvalidEscape(c) => write!(f, "unknown escape sequence `\\{}`", c),
+        }
+    }
+}
+
+impl Display for LexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} on line {}, column {}", self.err, self.span.line, self.span.column)
+    }
+}
+
+impl std::error::Error for LexError {}
+
+pub type LexResult<T> = Result<T, LexError>;
 
 /// The main `Lexer` that handles producing `Token` instances.
 /// 
-/// Keeps track of the current column and holds the current character in memory.
+/// Keeps track of the current position (byte offset, line and column) and holds the current character in memory.
 #[derive(Debug)]
 pub struct Lexer<'l> {
     source: Peekable<Chars<'l>>,
+    file: FileId,
+    offset: usize,
+    next_offset: usize,
     line: usize,
     column: usize,
     current: char,
@@ -20,8 +62,16 @@ impl<'l> Lexer<'l> {
     /// The `source` is converted into a `Peekable<Chars<'l>>` to make tracking current
     /// column position easier.
     pub fn new(source: &'l str) -> Self {
+        Self::with_file(source, FileId::default())
+    }
+
+    /// Create a new `Lexer` whose spans point into the file identified by `file`.
+    pub fn with_file(source: &'l str, file: FileId) -> Self {
         let mut this = Self {
             source: source.chars().peekable(),
+            file,
+            offset: 0,
+            next_offset: 0,
             column: 0,
             line: 1,
             current: '\0',
@@ -35,17 +85,26 @@ impl<'l> Lexer<'l> {
     /// 
     /// Also increments line and column counters to keep track of location.
     fn read(&mut self) -> Option<char> {
-        if let Some(c) = self.source.next() {
-            self.current = c;
-            self.column += 1;
+        self.offset = self.next_offset;
 
-            if c == '\n' {
+        if let Some(c) = self.source.next() {
+            // A newline belongs to the end of its own line, so we only move down once we're past it.
+            if self.current == '\n' {
                 self.line += 1;
                 self.column = 0;
             }
 
+            self.current = c;
+            self.column += 1;
+            self.next_offset += c.len_utf8();
+
             Some(self.current)
         } else {
+            // Step past the final character so that tokens ending the source get the same exclusive end column.
+            if self.current != '\0' {
+                self.column += 1;
+            }
+
             self.current = '\0';
 
             None
@@ -59,6 +118,94 @@ impl<'l> Lexer<'l> {
         }
     }
 
+    /// Skip over whitespace and comments, collecting any `///` doc comments along the way.
+    /// 
+    /// The lines of consecutive doc comments are joined with a newline and returned so that they
+    /// can be attached to the next token.
+    fn skip_trivia(&mut self) -> LexResult<Option<String>> {
+        let mut doc: Option<String> = None;
+
+        loop {
+            self.skip_whitespace();
+
+            if self.current != '/' {
+                break;
+            }
+
+            match self.source.peek() {
+                Some('/') => {
+                    if let Some(line) = self.skip_line_comment() {
+                        match doc.as_mut() {
+                            Some(doc) => {
+                                doc.push('\n');
+                                doc.push_str(&line);
+                            },
+                            None => doc = Some(line),
+                        }
+                    }
+                },
+                Some('*') => self.skip_block_comment()?,
+                _ => break,
+            }
+        }
+
+        Ok(doc)
+    }
+
+    /// Skip over a `//` comment, returning its contents if it was a `///` doc comment.
+    fn skip_line_comment(&mut self) -> Option<String> {
+        // Skip over the `//` characters.
+        self.read();
+        self.read();
+
+        // A `////` comment is a regular comment, not a doc comment.
+        let is_doc = self.current == '/' && self.source.peek() != Some(&'/');
+
+        if is_doc {
+            self.read();
+        }
+
+        let mut buffer = String::new();
+
+        while self.current != '\n' && self.current != '\0' {
+            buffer.push(self.current);
+            self.read();
+        }
+
+        if is_doc {
+            Some(buffer.strip_prefix(' ').unwrap_or(&buffer).trim_end().to_owned())
+        } else {
+            None
+        }
+    }
+
+    /// Skip over a `/* */` comment. Block comments can be nested, so every `/*` needs a matching `*/`.
+    fn skip_block_comment(&mut self) -> LexResult<()> {
+        let position = self.pos();
+        let mut depth = 0;
+
+        loop {
+            match (self.current, self.source.peek()) {
+                ('/', Some('*')) => {
+                    depth += 1;
+                    self.read();
+                },
+                ('*', Some('/')) => {
+                    depth -= 1;
+                    self.read();
+                },
+                ('\0', _) => return Err(self.error(position, LexErrorType::UnterminatedComment)),
+                _ => {},
+            }
+
+            self.read();
+
+            if depth == 0 {
+                return Ok(())
+            }
+        }
+    }
+
     fn parse_identifier_or_keyword(&mut self) -> Token {
         let position = self.pos();
         let mut buffer = String::from(self.current);
@@ -76,35 +223,37 @@ impl<'l> Lexer<'l> {
         }
 
         if let Some(kind) = keyword(&buffer) {
-            Token::new(kind, position.0, (position.1, self.column))
+            Token::new(kind, self.span_from(position))
         } else {
-            Token::new(TokenKind::Identifier(buffer), position.0, (position.1, self.column))
+            Token::new(TokenKind::Identifier(buffer), self.span_from(position))
         }
     }
 
-    fn parse_symbol(&mut self) -> Token {
+    fn parse_symbol(&mut self) -> LexResult<Token> {
         let position = self.pos();
         let buffer = String::from(self.current);
 
         self.read();
 
-        if ! is_valid_symbol_char(self.current) {
-            return Token::new(symbol(&buffer).unwrap(), position.0, (position.1, self.column))
-        }
-
-        let mut multi = String::from(&buffer);
-        multi.push(self.current);
+        if is_valid_symbol_char(self.current) {
+            let mut multi = String::from(&buffer);
+            multi.push(self.current);
 
-        if symbol(&multi).is_some() {
-            self.read();
+            if let Some(kind) = symbol(&multi) {
+                self.read();
 
-            return Token::new(symbol(&multi).unwrap(), position.0, (position.1, self.column))
+                return Ok(Token::new(kind, self.span_from(position)))
+            }
         }
 
-        Token::new(symbol(&buffer).unwrap(), position.0, (position.1, self.column))
+        match symbol(&buffer) {
+            Some(kind) => Ok(Token::new(kind, self.span_from(position))),
+            // Characters such as `&` and `|` are only valid as part of a longer symbol.
+            None => Err(self.error(position, LexErrorType::UnknownCharacter(buffer.chars().next().unwrap()))),
+        }
     }
 
-    fn parse_numeric(&mut self) -> Token {
+    fn parse_numeric(&mut self) -> LexResult<Token> {
         let position = self.pos();
         let mut buffer = String::from(self.current);
 
@@ -113,35 +262,32 @@ impl<'l> Lexer<'l> {
                 break;
             }
 
-            if ! self.current.is_numeric() && self.current != '.' {
-                break;
-            }
+            // A `.` only belongs to the number if a digit follows it, otherwise it is a method call, e.g. `5.floor()`.
+            let is_decimal_point = self.current == '.' && matches!(self.source.peek(), Some(c) if c.is_numeric());
 
-            if self.current == '.' && buffer.contains('.') {
-                panic!("Cannot have more than 1 `.` in a numeric value.");
-            }
-
-            if self.current == '.' {
-                self.read();
-                buffer.push('.');
+            if ! self.current.is_numeric() && ! is_decimal_point {
+                break;
             }
 
             buffer.push(self.current);
         }
 
-        // We can safely unwrap the value here as we escape from the loop early 
-        // when a non-numeric value is encountered.
-        Token::new(TokenKind::Number(buffer.parse().unwrap()), position.0, (position.1, self.column))
+        match buffer.parse() {
+            Ok(n) => Ok(Token::new(TokenKind::Number(n), self.span_from(position))),
+            Err(_) => Err(self.error(position, LexErrorType::InvalidNumber(buffer))),
+        }
     }
 
-    fn parse_string(&mut self) -> Token {
+    fn parse_string(&mut self) -> LexResult<Token> {
         let position = self.pos();
         let mut buffer = String::new();
         let mut escaping = false;
+        let mut escape = position;
+        let mut error = None;
 
         loop {
             if self.read().is_none() {
-                break;
+                return Err(self.error(position, LexErrorType::UnterminatedString))
             }
 
             if escaping {
@@ -149,7 +295,17 @@ impl<'l> Lexer<'l> {
                     't' => '\t',
                     'n' => '\n',
                     'r' => '\r',
-                    _ => self.current
+                    '0' => '\0',
+                    '"' | '\\' => self.current,
+                    c => {
+                        // Keep reading until the end of the string so that lexing can continue after it.
+                        error.get_or_insert_with(|| LexError {
+                            span: Span { end: self.offset + c.len_utf8(), ..escape },
+                            err: LexErrorType::InvalidEscape(c),
+                        });
+
+                        c
+                    },
                 });
 
                 escaping = false;
@@ -165,6 +321,7 @@ impl<'l> Lexer<'l> {
 
             if self.current == '\\' {
                 escaping = true;
+                escape = self.pos();
 
                 continue;
             }
@@ -172,27 +329,57 @@ impl<'l> Lexer<'l> {
             buffer.push(self.current)
         }
 
-        Token::new(TokenKind::String(buffer), position.0, (position.1, self.column))
+        match error {
+            Some(error) => Err(error),
+            None => Ok(Token::new(TokenKind::String(buffer), self.span_from(position))),
+        }
+    }
+
+    /// Create a `LexError` that starts at `position` and ends at the current character.
+    fn error(&self, position: Span, err: LexErrorType) -> LexError {
+        LexError { span: self.span_from(position), err }
+    }
+
+    /// Create a `Span` that starts at `position` and ends at the current character.
+    fn span_from(&self, position: Span) -> Span {
+        Span { end: self.offset, ..position }
     }
 
-    fn pos(&self) -> Span {
-        (self.line, self.column)
+    /// The position of the current character, as an empty `Span`.
+    pub(crate) fn pos(&self) -> Span {
+        Span::new(self.file, self.offset, self.offset, self.line, self.column)
     }
 }
 
 impl<'l> Iterator for Lexer<'l> {
-    type Item = Token;
+    type Item = LexResult<Token>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.skip_whitespace();
+        let doc = match self.skip_trivia() {
+            Ok(doc) => doc,
+            Err(e) => return Some(Err(e)),
+        };
 
-        Some(match self.current {
-            _ if is_valid_identifier_char(self.current) => self.parse_identifier_or_keyword(),
+        let token = match self.current {
+            '\0' if self.source.peek().is_none() => return None,
+            _ if is_valid_identifier_char(self.current) => Ok(self.parse_identifier_or_keyword()),
             _ if is_valid_symbol_char(self.current) => self.parse_symbol(),
             _ if self.current.is_numeric() => self.parse_numeric(),
             _ if is_valid_string_wrapper(self.current) => self.parse_string(),
-            _ => return None
-        })
+            c => {
+                let position = self.pos();
+
+                // Skip over the character so that the next call can carry on lexing.
+                self.read();
+
+                Err(self.error(position, LexErrorType::UnknownCharacter(c)))
+            }
+        };
+
+        Some(token.map(|mut token| {
+            token.doc = doc;
+            token
+        }))
     }
 }
 
@@ -246,6 +433,9 @@ fn symbol(s: &str) -> Option<TokenKind> {
     })
 }
 
+/// Every keyword of the language, in the order that `keyword` matches them.
+pub const KEYWORDS: &[&str] = &["fn", "let", "if", "else", "while", "return", "break", "continue", "true", "false", "import", "from", "as", "export"];
+
 fn keyword(s: &str) -> Option<TokenKind> {
     Some(match s {
         "fn" => TokenKind::Fn,
@@ -260,6 +450,8 @@ fn keyword(s: &str) -> Option<TokenKind> {
         "false" => TokenKind::False,
         "import" => TokenKind::Import,
         "from" => TokenKind::From,
+        "as" => TokenKind::As,
+        "export" => TokenKind::Export,
         _ => return None
     })
 }
@@ -270,7 +462,7 @@ mod tests {
 
     #[test]
     fn keywords() {
-        matches("fn if else while return break continue let true false use from", vec![
+        matches("fn if else while return break continue let true false import from as export", vec![
             TokenKind::Fn,
             TokenKind::If,
             TokenKind::Else,
@@ -283,9 +475,16 @@ mod tests {
             TokenKind::False,
             TokenKind::Import,
             TokenKind::From,
+            TokenKind::As,
+            TokenKind::Export,
         ]);
     }
 
+    #[test]
+    fn keyword_list() {
+        assert!(KEYWORDS.iter().all(|k| keyword(k).is_some()));
+    }
+
     #[test]
     fn symbols() {
         matches("+ - * / % ** ( ) { } [ ] : :: ; , = == != > < >= <= . ! -> += -= *= /=", vec![
@@ -340,14 +539,137 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn line_comments() {
+        matches("// a comment\n1 // another comment\n2 //", vec![
+            TokenKind::Number(1.0),
+            TokenKind::Number(2.0),
+        ]);
+
+        matches("1 / 2 /= 3", vec![
+            TokenKind::Number(1.0),
+            TokenKind::Slash,
+            TokenKind::Number(2.0),
+            TokenKind::SlashEquals,
+            TokenKind::Number(3.0),
+        ]);
+    }
+
+    #[test]
+    fn block_comments() {
+        matches("1 /* a comment */ 2 /* a\nmulti-line\ncomment */ 3", vec![
+            TokenKind::Number(1.0),
+            TokenKind::Number(2.0),
+            TokenKind::Number(3.0),
+        ]);
+
+        matches("1 /* outer /* inner */ still a comment */ 2", vec![
+            TokenKind::Number(1.0),
+            TokenKind::Number(2.0),
+        ]);
+
+        matches("1 /** stars **/ 2 /**/", vec![
+            TokenKind::Number(1.0),
+            TokenKind::Number(2.0),
+        ]);
+    }
+
+    #[test]
+    fn doc_comments() {
+        let tokens = Lexer::new("/// Says hello.\n/// Twice.\nfn hello() {}\n//// Not a doc comment.\nlet a = 1").collect::<LexResult<Vec<Token>>>().unwrap();
+
+        assert_eq!(tokens[0].kind, TokenKind::Fn);
+        assert_eq!(tokens[0].doc.as_deref(), Some("Says hello.\nTwice."));
+        assert!(tokens[1..].iter().all(|t| t.doc.is_none()));
+
+        let tokens = Lexer::new("/// Doc.\n// Regular.\n/* Block. */\nlet a = 1").collect::<LexResult<Vec<Token>>>().unwrap();
+
+        assert_eq!(tokens[0].kind, TokenKind::Let);
+        assert_eq!(tokens[0].doc.as_deref(), Some("Doc."));
+    }
+
+    #[test]
+    fn spans() {
+        let tokens = Lexer::with_file("let café = \"é\"\n  fn", FileId(3)).collect::<LexResult<Vec<Token>>>().unwrap();
+
+        assert_eq!(tokens.iter().map(|t| t.span).collect::<Vec<Span>>(), vec![
+            Span::new(FileId(3), 0, 3, 1, 1),
+            Span::new(FileId(3), 4, 9, 1, 5),
+            Span::new(FileId(3), 10, 11, 1, 10),
+            Span::new(FileId(3), 12, 16, 1, 12),
+            Span::new(FileId(3), 19, 21, 2, 3),
+        ]);
+    }
+
+    #[test]
+    fn method_calls_on_numbers() {
+        matches("5.floor()", vec![
+            TokenKind::Number(5.0),
+            TokenKind::Dot,
+            TokenKind::Identifier("floor".into()),
+            TokenKind::LeftParen,
+            TokenKind::RightParen,
+        ]);
+    }
+
+    #[test]
+    fn invalid_numbers() {
+        assert_eq!(errors("1.2.3"), vec![
+            LexError { span: span(0, 5, 1, 1), err: LexErrorType::InvalidNumber("1.2.3".into()) },
+        ]);
+    }
+
+    #[test]
+    fn unterminated_strings() {
+        assert_eq!(errors("let a = \"hello"), vec![
+            LexError { span: span(8, 14, 1, 9), err: LexErrorType::UnterminatedString },
+        ]);
+    }
+
+    #[test]
+    fn invalid_escapes() {
+        assert_eq!(errors(r##""a\qb" "c""##), vec![
+            LexError { span: span(2, 4, 1, 3), err: LexErrorType::InvalidEscape('q') },
+        ]);
+
+        // Lexing carries on after the invalid string.
+        assert_eq!(Lexer::new(r##""a\qb" "c""##).nth(1).unwrap().unwrap().kind, TokenKind::String("c".into()));
+    }
+
+    #[test]
+    fn unknown_characters() {
+        assert_eq!(errors("1 # 2 & 3 | 4"), vec![
+            LexError { span: span(2, 3, 1, 3), err: LexErrorType::UnknownCharacter('#') },
+            LexError { span: span(6, 7, 1, 7), err: LexErrorType::UnknownCharacter('&') },
+            LexError { span: span(10, 11, 1, 11), err: LexErrorType::UnknownCharacter('|') },
+        ]);
+
+        assert_eq!(Lexer::new("1 # 2").filter_map(Result::ok).count(), 2);
+    }
+
+    #[test]
+    fn unterminated_comments() {
+        assert_eq!(errors("1 /* /* */"), vec![
+            LexError { span: span(2, 10, 1, 3), err: LexErrorType::UnterminatedComment },
+        ]);
+    }
+
     fn matches(source: &str, expected: Vec<TokenKind>) {
         let mut lexer = Lexer::new(source); 
         let mut kinds = Vec::new();
 
         while let Some(t) = lexer.next() {
-            kinds.push(t.kind);
+            kinds.push(t.unwrap().kind);
         }
 
         assert_eq!(expected, kinds)
     }
+
+    fn span(start: usize, end: usize, line: usize, column: usize) -> Span {
+        Span::new(FileId::default(), start, end, line, column)
+    }
+
+    fn errors(source: &str) -> Vec<LexError> {
+        Lexer::new(source).filter_map(Result::err).collect()
+    }
 }
\ No newline at end of file
@@ -1,3 +1,5 @@
+mod span;
+mod node;
 mod token;
 mod lexer;
 mod parser;
@@ -5,15 +7,23 @@ mod statement;
 mod expression;
 mod r#type;
 mod passes;
+mod diagnostic;
+mod format;
+mod json;
 
-pub use token::{TokenKind, Token, Span};
-pub use lexer::Lexer;
-pub use statement::{Statement, Parameter};
+pub use span::{Span, FileId};
+pub use node::Node;
+pub use token::{TokenKind, Token};
+pub use lexer::{Lexer, LexError, LexErrorType, KEYWORDS};
+pub use statement::{Statement, Parameter, Imports, Import};
 pub use expression::{Expression, Op};
 pub use r#type::Type;
 pub use parser::{Parser, ParserError, ParserErrorType, Program};
+pub use diagnostic::Diagnostic;
+pub use format::format;
+pub use json::Json;
 
-pub fn parse(source: &str) -> Result<Vec<Statement>, ParserError> {
+pub fn parse(source: &str) -> Result<Program, Vec<ParserError>> {
     let lexer = Lexer::new(source);
 
     let mut parser = Parser::new(lexer);
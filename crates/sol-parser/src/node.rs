@@ -0,0 +1,40 @@
+use crate::Span;
+
+/// Wraps a `Statement` or `Expression` along with the `Span` of source code that it was parsed from.
+/// 
+/// Two nodes are equal when their kinds are equal, the spans are not compared. This means that trees
+/// can be compared structurally, regardless of how the source code was laid out.
+#[derive(Debug, Clone)]
+pub struct Node<T> {
+    pub kind: T,
+    pub span: Span,
+}
+
+impl<T> Node<T> {
+    pub fn new(kind: T, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl<T: PartialEq> PartialEq for Node<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl<T: PartialEq> PartialEq<T> for Node<T> {
+    fn eq(&self, other: &T) -> bool {
+        self.kind == *other
+    }
+}
+
+/// Nodes created from a bare kind have an empty span, which is useful for nodes generated by the compiler.
+impl<T> From<T> for Node<T> {
+    fn from(kind: T) -> Self {
+        Self::new(kind, Span::default())
+    }
+}
\ No newline at end of file
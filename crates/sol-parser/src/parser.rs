@@ -1,22 +1,56 @@
-use crate::{Statement, Expression, Token, TokenKind, Lexer, Type, Parameter, Span};
+use crate::{Statement, Expression, Token, TokenKind, Lexer, LexError, LexErrorType, Type, Parameter, Imports, Import, Span, Node, Op};
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 
-pub type Program = Vec<Statement>;
+pub type Program = Vec<Node<Statement>>;
 
 #[derive(Debug, Clone)]
 pub struct ParserError {
-    pub line: usize,
     pub span: Span,
     pub err: ParserErrorType,
 }
 
+/// The different kinds of `ParserError`.
+/// 
+/// `UnexpectedToken` holds the token that was found, along with a description of what was expected instead.
 #[derive(Debug, Clone)]
 pub enum ParserErrorType {
     InvalidBreakableScope,
     InvalidContinuableScope,
-    UnexpectedToken(String, Option<String>),
+    UnexpectedToken(TokenKind, Option<String>),
     NestedFunctionDefinition,
-    ExpectedIdentifier,
+    NestedExport,
+    ExpectedIdentifier(TokenKind),
+    Lex(LexErrorType),
+}
+
+impl Display for ParserErrorType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParserErrorType::InvalidBreakableScope => write!(f, "`break` outside of a loop"),
+            ParserErrorType::InvalidContinuableScope => write!(f, "`continue` outside of a loop"),
+            ParserErrorType::UnexpectedToken(found, Some(expected)) => write!(f, "expected {} but found {}", expected, found),
+            ParserErrorType::UnexpectedToken(found, None) => write!(f, "unexpected {}", found),
+            ParserErrorType::NestedFunctionDefinition => write!(f, "functions cannot be defined inside of other functions"),
+            ParserErrorType::NestedExport => write!(f, "`export` is only allowed at the top level of a file"),
+            ParserErrorType::ExpectedIdentifier(found) => write!(f, "expected an identifier but found {}", found),
+            ParserErrorType::Lex(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Display for ParserError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} on line {}, column {}", self.err, self.span.line, self.span.column)
+    }
+}
+
+impl std::error::Error for ParserError {}
+
+impl From<LexError> for ParserError {
+    fn from(LexError { span, err }: LexError) -> Self {
+        Self { span, err: ParserErrorType::Lex(err) }
+    }
 }
 
 type BindingPower = u8;
@@ -27,8 +61,12 @@ pub struct Parser<'p> {
     lexer: Lexer<'p>,
     current: Token,
     peek: Token,
+    previous: Span,
     in_breakable_scope: bool,
     scope_depth: usize,
+    block_depth: usize,
+    errors: Vec<ParserError>,
+    consumed: usize,
 }
 
 #[allow(dead_code)]
@@ -38,48 +76,91 @@ impl<'p> Parser<'p> {
             lexer,
             current: Token::eof(),
             peek: Token::eof(),
+            previous: Span::default(),
             in_breakable_scope: false,
             scope_depth: 0,
+            block_depth: 0,
+            errors: Vec::new(),
+            consumed: 0,
         }
     }
 
+    /// Read in the next token.
+    /// 
+    /// Lexer errors don't stop the parser, they are recorded and the invalid input is skipped over.
     pub fn read(&mut self) {
-        self.current = std::mem::replace(&mut self.peek, if let Some(t) = self.lexer.next() { t } else { Token::eof() });
-    }
+        let next = loop {
+            match self.lexer.next() {
+                Some(Ok(t)) => break t,
+                Some(Err(e)) => self.errors.push(e.into()),
+                None => break Token::new(TokenKind::Eof, self.lexer.pos()),
+            }
+        };
 
-    fn parse_statement(&mut self) -> ParserResult<Statement> {
-        Ok(match self.current.kind {
-            TokenKind::Let => self.parse_let()?,
-            TokenKind::Fn => self.parse_fn()?,
-            TokenKind::If => self.parse_if()?,
-            TokenKind::While => self.parse_while()?,
-            TokenKind::Import => {
-                self.read();
+        self.previous = self.current.span;
+        self.current = std::mem::replace(&mut self.peek, next);
+        self.consumed += 1;
+    }
 
-                let mut imports = Vec::new();
+    /// Parse a statement, recovering from any error by recording it and skipping ahead to the
+    /// start of the next statement. A `Statement::Error` is returned in place of the broken statement.
+    fn parse_statement_or_recover(&mut self) -> Node<Statement> {
+        let start = self.current.span;
+        let consumed = self.consumed;
+        let in_breakable_scope = self.in_breakable_scope;
+        let scope_depth = self.scope_depth;
+        let block_depth = self.block_depth;
+
+        match self.parse_statement() {
+            Ok(statement) => statement,
+            Err(e) => {
+                self.errors.push(e);
+
+                self.in_breakable_scope = in_breakable_scope;
+                self.scope_depth = scope_depth;
+                self.block_depth = block_depth;
+
+                // Always make progress, otherwise an error on a synchronizing token would loop forever.
+                if self.consumed == consumed && self.current.kind != TokenKind::Eof {
+                    self.read();
+                }
 
-                while self.current.kind != TokenKind::From {
-                    if imports.len() > 0 {
-                        self.expect(TokenKind::Comma)?;
-                    }
+                self.synchronize();
 
-                    let import = self.identifier()?;
+                self.node(Statement::Error, start)
+            }
+        }
+    }
 
-                    imports.push(import);
-                }
+    /// Skip tokens until one that can start a new statement, or close the current block, is found.
+    fn synchronize(&mut self) {
+        while ! matches!(self.current.kind, TokenKind::Let | TokenKind::Fn | TokenKind::If | TokenKind::While | TokenKind::Import | TokenKind::Export | TokenKind::RightBrace | TokenKind::Eof) {
+            self.read();
+        }
+    }
 
-                self.expect(TokenKind::From)?;
+    /// Wrap `kind` in a `Node` spanning from `start` to the end of the last token that was read.
+    fn node<T>(&self, kind: T, start: Span) -> Node<T> {
+        Node::new(kind, start.to(self.previous))
+    }
 
-                let module = self.string()?;
+    fn parse_statement(&mut self) -> ParserResult<Node<Statement>> {
+        let start = self.current.span;
 
-                Statement::Use {
-                    module,
-                    imports
-                }
-            },
+        let statement = match self.current.kind {
+            TokenKind::Let => self.parse_let()?,
+            TokenKind::Fn => self.parse_fn()?,
+            TokenKind::If => self.parse_if()?,
+            TokenKind::While => self.parse_while()?,
+            TokenKind::Export => self.parse_export()?,
+            TokenKind::Import => self.parse_import()?,
             TokenKind::Break => {
+                // The statement itself is well-formed, so we report the error and keep going.
                 if ! self.in_breakable_scope {
-                    return Err(ParserError { line: self.current.line, span: self.current.span, err: ParserErrorType::InvalidBreakableScope })
+                    self.errors.push(ParserError { span: self.current.span, err: ParserErrorType::InvalidBreakableScope });
+                    self.read();
+
+                    return Ok(self.node(Statement::Error, start))
                 }
 
                 self.read();
@@ -88,7 +169,10 @@ impl<'p> Parser<'p> {
             },
             TokenKind::Continue => {
                 if ! self.in_breakable_scope {
-                    return Err(ParserError { line: self.current.line, span: self.current.span, err: ParserErrorType::InvalidContinuableScope })
+                    self.errors.push(ParserError { span: self.current.span, err: ParserErrorType::InvalidContinuableScope });
+                    self.read();
+
+                    return Ok(self.node(Statement::Error, start))
                 }
 
                 self.read();
@@ -105,26 +189,30 @@ impl<'p> Parser<'p> {
             _ => {
                 Statement::Expression { expression: self.expression(0)? }
             },
-        })
+        };
+
+        Ok(self.node(statement, start))
     }
 
     fn parse_let(&mut self) -> ParserResult<Statement> {
+        let doc = self.current.doc.take();
+
         self.read();
 
         let identifier = self.identifier()?;
-        let r#_type = self.r#type()?;
+        let r#type = self.r#type()?;
 
         self.expect(TokenKind::Equals)?;
 
         let expression = self.expression(0)?;
 
-        Ok(Statement::Let { identifier, initial: expression })
+        Ok(Statement::Let { identifier, r#type, initial: expression, doc })
     }
 
     fn parse_fn(&mut self) -> ParserResult<Statement> {
+        // Nested functions are still parsed so that the rest of the outer function can be checked.
         if self.scope_depth > 0 {
-            return Err(ParserError {
-                line: self.current.line,
+            self.errors.push(ParserError {
                 span: self.current.span,
                 err: ParserErrorType::NestedFunctionDefinition,
             });
@@ -132,6 +220,8 @@ impl<'p> Parser<'p> {
 
         self.scope_depth += 1;
 
+        let doc = self.current.doc.take();
+
         self.read();
 
         let identifier = self.identifier()?;
@@ -146,17 +236,123 @@ impl<'p> Parser<'p> {
 
         self.expect(TokenKind::LeftBrace)?;
 
-        let body = self.block(TokenKind::RightBrace)?;
-
-        self.expect(TokenKind::RightBrace)?;
+        let body = self.function_body()?;
 
         self.scope_depth -= 1;
 
         Ok(Statement::Function {
-            identifier, parameters, return_type, body
+            identifier, parameters, return_type, body, doc
+        })
+    }
+
+    fn parse_import(&mut self) -> ParserResult<Statement> {
+        self.read();
+
+        let imports = match self.current.kind {
+            // A bare import doesn't declare anything, so it doesn't have a `from` either.
+            TokenKind::String(_) => return Ok(Statement::Use { module: self.string()?, imports: Imports::Bare }),
+            TokenKind::Asterisk => {
+                self.read();
+                self.expect(TokenKind::As)?;
+
+                Imports::Namespace(self.identifier()?)
+            },
+            TokenKind::LeftBrace => {
+                self.read();
+
+                let imports = self.imports(TokenKind::RightBrace)?;
+
+                self.expect(TokenKind::RightBrace)?;
+
+                Imports::Named(imports)
+            },
+            _ => Imports::Named(self.imports(TokenKind::From)?),
+        };
+
+        self.expect(TokenKind::From)?;
+
+        let module = self.string()?;
+
+        Ok(Statement::Use {
+            module,
+            imports
         })
     }
 
+    /// The names in an import like `a, b as c`, up to the `end` of the list.
+    fn imports(&mut self, end: TokenKind) -> ParserResult<Vec<Import>> {
+        let mut imports = Vec::new();
+
+        while self.current.kind != end {
+            if ! imports.is_empty() {
+                self.expect(TokenKind::Comma)?;
+            }
+
+            let name = self.identifier()?;
+            let alias = match self.current.kind {
+                TokenKind::As => {
+                    self.read();
+
+                    Some(self.identifier()?)
+                },
+                _ => None,
+            };
+
+            imports.push(Import::new(name, alias));
+        }
+
+        Ok(imports)
+    }
+
+    fn parse_export(&mut self) -> ParserResult<Statement> {
+        // Like nested functions, the statement is still parsed so that it can be checked.
+        if self.block_depth > 0 {
+            self.errors.push(ParserError {
+                span: self.current.span,
+                err: ParserErrorType::NestedExport,
+            });
+        }
+
+        // The doc comment is written before `export`, but it documents the declaration.
+        let doc = self.current.doc.take();
+
+        self.read();
+
+        if doc.is_some() {
+            self.current.doc = doc;
+        }
+
+        let start = self.current.span;
+
+        let declaration = match self.current.kind {
+            TokenKind::Let => self.parse_let()?,
+            TokenKind::Fn => self.parse_fn()?,
+            TokenKind::LeftBrace => {
+                self.read();
+
+                let mut names = Vec::new();
+
+                while self.current.kind != TokenKind::RightBrace {
+                    names.push(self.identifier()?);
+
+                    if self.current.kind != TokenKind::RightBrace {
+                        self.expect(TokenKind::Comma)?;
+                    }
+                }
+
+                self.expect(TokenKind::RightBrace)?;
+
+                return Ok(Statement::Export { declaration: None, names })
+            },
+            _ => return Err(ParserError {
+                span: self.current.span,
+                err: ParserErrorType::UnexpectedToken(self.current.kind.clone(), Some("`fn`, `let` or `{`".to_owned())),
+            }),
+        };
+
+        Ok(Statement::Export { declaration: Some(Box::new(self.node(declaration, start))), names: Vec::new() })
+    }
+
     fn parse_if(&mut self) -> ParserResult<Statement> {
         self.read();
 
@@ -175,7 +371,10 @@ impl<'p> Parser<'p> {
 
             // If we see another `if` token, then we're going to parse an `else if` statement.
             if self.current.kind == TokenKind::If {
-                otherwise = vec![self.parse_if()?];
+                let start = self.current.span;
+                let statement = self.parse_if()?;
+
+                otherwise = vec![self.node(statement, start)];
             } else {
                 self.expect(TokenKind::LeftBrace)?;
 
@@ -194,14 +393,15 @@ impl<'p> Parser<'p> {
         let condition = self.expression(0)?;
 
         self.expect(TokenKind::LeftBrace)?;
-        
-        self.in_breakable_scope = true;
+
+        // Restore the previous scope afterwards, otherwise a nested loop would end the outer loop's scope.
+        let in_breakable_scope = std::mem::replace(&mut self.in_breakable_scope, true);
 
         let then = self.block(TokenKind::RightBrace)?;
 
         self.expect(TokenKind::RightBrace)?;
 
-        self.in_breakable_scope = false;
+        self.in_breakable_scope = in_breakable_scope;
 
         Ok(Statement::While { condition, then })
     }
@@ -212,12 +412,14 @@ impl<'p> Parser<'p> {
 
             Ok(())
         } else {
-            return Err(ParserError { line: self.current.line, span: self.current.span, err: ParserErrorType::UnexpectedToken(format!("{:?}", self.current.kind), Some(format!("{:?}", kind))) })
+            Err(ParserError { span: self.current.span, err: ParserErrorType::UnexpectedToken(self.current.kind.clone(), Some(kind.to_string())) })
         }
     }
 
-    fn expression(&mut self, bp: u8) -> ParserResult<Expression> {
-        let mut lhs = match self.current.kind.clone() {
+    fn expression(&mut self, bp: u8) -> ParserResult<Node<Expression>> {
+        let start = self.current.span;
+
+        let lhs = match self.current.kind.clone() {
             TokenKind::Number(n) => {
                 self.read();
 
@@ -296,16 +498,13 @@ impl<'p> Parser<'p> {
                     self.read();
 
                     let expression = self.expression(0)?;
+                    let span = expression.span;
 
-                    vec![Statement::Return { expression }]
+                    vec![Node::new(Statement::Return { expression }, span)]
                 } else {
                     self.expect(TokenKind::LeftBrace)?;
-                    
-                    let body = self.block(TokenKind::RightBrace)?;
-
-                    self.expect(TokenKind::RightBrace)?;
 
-                    body
+                    self.function_body()?
                 };
 
                 Expression::Closure(params, body)
@@ -317,7 +516,8 @@ impl<'p> Parser<'p> {
 
                 self.expect(TokenKind::RightParen)?;
 
-                expression
+                // The node is rebuilt below so that its span includes the parentheses.
+                expression.kind
             },
             _ if is_prefix(&self.current.kind) => {
                 let kind = self.current.kind.clone();
@@ -328,11 +528,16 @@ impl<'p> Parser<'p> {
 
                 let rhs = self.expression(rbp)?;
 
-                prefix(&kind, rhs)
+                prefix(&kind, start, rhs)?
             },
-            _ => todo!("{:?}", self.current.kind),
+            _ => return Err(ParserError {
+                span: self.current.span,
+                err: ParserErrorType::UnexpectedToken(self.current.kind.clone(), Some("an expression".to_owned())),
+            }),
         };
 
+        let mut lhs = self.node(lhs, start);
+
         loop {
             if self.current.kind == TokenKind::Eof {
                 break;
@@ -347,7 +552,9 @@ impl<'p> Parser<'p> {
 
                 self.read();
 
-                lhs = postfix(self, lhs, &op)?;
+                let kind = postfix(self, lhs, &op)?;
+
+                lhs = self.node(kind, start);
 
                 continue;
             }
@@ -357,11 +564,13 @@ impl<'p> Parser<'p> {
                     break;
                 }
 
+                let span = self.current.span;
+
                 self.read();
 
                 let rhs = self.expression(rbp)?;
 
-                lhs = infix(lhs, &op, rhs);
+                lhs = self.node(infix(lhs, &op, span, rhs)?, start);
 
                 continue;
             }
@@ -379,7 +588,7 @@ impl<'p> Parser<'p> {
 
                 Ok(i)
             },
-            _ => Err(ParserError { line: self.current.line, span: self.current.span, err: ParserErrorType::UnexpectedToken(format!("{:?}", self.current.kind), Some("String".to_owned())) })
+            _ => Err(ParserError { span: self.current.span, err: ParserErrorType::UnexpectedToken(self.current.kind.clone(), Some("a string".to_owned())) })
         }
     }
 
@@ -390,11 +599,11 @@ impl<'p> Parser<'p> {
 
                 Ok(i)
             },
-            _ => Err(ParserError { line: self.current.line, span: self.current.span, err: ParserErrorType::ExpectedIdentifier })
+            _ => Err(ParserError { span: self.current.span, err: ParserErrorType::ExpectedIdentifier(self.current.kind.clone()) })
         }
     }
 
-    fn args(&mut self) -> ParserResult<Vec<Expression>> {
+    fn args(&mut self) -> ParserResult<Vec<Node<Expression>>> {
         let mut args = Vec::new();
 
         loop {
@@ -435,16 +644,36 @@ impl<'p> Parser<'p> {
         Ok(parameters)
     }
 
-    fn block(&mut self, end: TokenKind) -> ParserResult<Vec<Statement>> {
+    fn block(&mut self, end: TokenKind) -> ParserResult<Vec<Node<Statement>>> {
         let mut block = Vec::new();
 
-        while self.current.kind != end {
-            block.push(self.parse_statement()?);
+        self.block_depth += 1;
+
+        while self.current.kind != end && self.current.kind != TokenKind::Eof {
+            block.push(self.parse_statement_or_recover());
         }
 
+        self.block_depth -= 1;
+
         Ok(block)
     }
 
+    /// Parse the body of a function or closure, up to and including the closing brace.
+    /// 
+    /// A function body starts a new scope, so a `break` or `continue` inside of it can't refer to a loop
+    /// that surrounds the function.
+    fn function_body(&mut self) -> ParserResult<Vec<Node<Statement>>> {
+        let in_breakable_scope = std::mem::replace(&mut self.in_breakable_scope, false);
+
+        let body = self.block(TokenKind::RightBrace)?;
+
+        self.expect(TokenKind::RightBrace)?;
+
+        self.in_breakable_scope = in_breakable_scope;
+
+        Ok(body)
+    }
+
     fn r#type(&mut self) -> ParserResult<Option<Type>> {
         if self.current.kind != TokenKind::Colon && self.current.kind != TokenKind::DoubleColon {
             Ok(None)
@@ -457,17 +686,32 @@ impl<'p> Parser<'p> {
         }
     }
 
-    pub fn parse(&mut self) -> ParserResult<Program> {
+    /// Parse the entire source, returning every error that was found if the source is invalid.
+    pub fn parse(&mut self) -> Result<Program, Vec<ParserError>> {
+        let (program, errors) = self.parse_partial();
+
+        if errors.is_empty() {
+            Ok(program)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Parse the entire source, recovering from errors where possible.
+    /// 
+    /// The returned `Program` contains a `Statement::Error` in place of each statement that could not be
+    /// parsed, which makes it useful for tooling that needs to work with broken source code.
+    pub fn parse_partial(&mut self) -> (Program, Vec<ParserError>) {
         let mut program = Vec::new();
 
         self.read();
         self.read();
 
         while self.current.kind != TokenKind::Eof {
-            program.push(self.parse_statement()?);
+            program.push(self.parse_statement_or_recover());
         }
 
-        Ok(program)
+        (program, std::mem::take(&mut self.errors))
     }
 }
 
@@ -482,13 +726,15 @@ fn prefix_binding_power(kind: &TokenKind) -> ((), u8) {
     }
 }
 
-fn prefix(kind: &TokenKind, rhs: Expression) -> Expression {
-    Expression::Prefix(kind.into(), Box::new(rhs))
+fn prefix(kind: &TokenKind, span: Span, rhs: Node<Expression>) -> ParserResult<Expression> {
+    Ok(Expression::Prefix(operator(kind, span)?, rhs.boxed()))
 }
 
 fn infix_binding_power(kind: &TokenKind) -> Option<(BindingPower, BindingPower)> {
     Some(match kind {
-        TokenKind::Asterisk | TokenKind::Slash | TokenKind::Percent | TokenKind::DoubleAsterisk => (13, 14),
+        // `**` binds tighter than `*` and is right associative, as in JavaScript.
+        TokenKind::DoubleAsterisk => (16, 15),
+        TokenKind::Asterisk | TokenKind::Slash | TokenKind::Percent => (13, 14),
         TokenKind::Plus | TokenKind::Minus => (11, 12),
         TokenKind::GreaterThan | TokenKind::GreaterThanEquals | TokenKind::LessThan | TokenKind::LessThanEquals => (9, 10),
         TokenKind::EqualsEquals | TokenKind::NotEquals => (7, 8),
@@ -499,11 +745,16 @@ fn infix_binding_power(kind: &TokenKind) -> Option<(BindingPower, BindingPower)>
     })
 }
 
-fn infix(lhs: Expression, kind: &TokenKind, rhs: Expression) -> Expression {
-    match kind {
+fn infix(lhs: Node<Expression>, kind: &TokenKind, span: Span, rhs: Node<Expression>) -> ParserResult<Expression> {
+    Ok(match kind {
         TokenKind::Equals => Expression::Assign(lhs.boxed(), rhs.boxed()),
-        _ => Expression::Infix(lhs.boxed(), kind.into(), rhs.boxed())
-    }
+        _ => Expression::Infix(lhs.boxed(), operator(kind, span)?, rhs.boxed())
+    })
+}
+
+/// The `Op` of the operator token at `span`.
+fn operator(kind: &TokenKind, span: Span) -> ParserResult<Op> {
+    Op::try_from(kind).map_err(|kind| ParserError { span, err: ParserErrorType::UnexpectedToken(kind, Some("an operator".to_owned())) })
 }
 
 fn postfix_binding_power(kind: &TokenKind) -> Option<(BindingPower, ())> {
@@ -513,7 +764,7 @@ fn postfix_binding_power(kind: &TokenKind) -> Option<(BindingPower, ())> {
     })
 }
 
-fn postfix(parser: &mut Parser, lhs: Expression, kind: &TokenKind) -> ParserResult<Expression> {
+fn postfix(parser: &mut Parser, lhs: Node<Expression>, kind: &TokenKind) -> ParserResult<Expression> {
     match kind {
         TokenKind::LeftParen => {
             let args = parser.args()?;
@@ -540,14 +791,14 @@ fn postfix(parser: &mut Parser, lhs: Expression, kind: &TokenKind) -> ParserResu
 
             Ok(Expression::Dot(lhs.boxed(), path.boxed()))
         },
-        _ => todo!()
+        _ => Err(ParserError { span: parser.previous, err: ParserErrorType::UnexpectedToken(kind.clone(), None) }),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Op;
+    use crate::{FileId, Op};
 
     macro_rules! map {
         ($($key:expr => $value:expr),+) => {
@@ -569,9 +820,9 @@ mod tests {
             Statement::Expression {
                 expression: Expression::Closure(vec![], vec![
                     Statement::Return {
-                        expression: Expression::String("testing".to_owned())
-                    }
-                ])
+                        expression: Expression::String("testing".to_owned()).into()
+                    }.into()
+                ]).into()
             }
         ]);
     }
@@ -584,7 +835,7 @@ mod tests {
         })
         "##), vec![
             Statement::Expression {
-                expression: Expression::Closure(vec![], vec![])
+                expression: Expression::Closure(vec![], vec![]).into()
             }
         ]);
 
@@ -596,7 +847,7 @@ mod tests {
             Statement::Expression {
                 expression: Expression::Closure(vec![
                     Parameter::new("name", None)
-                ], vec![])
+                ], vec![]).into()
             }
         ]);
 
@@ -609,7 +860,7 @@ mod tests {
                 expression: Expression::Closure(vec![
                     Parameter::new("name", None),
                     Parameter::new("age", None),
-                ], vec![])
+                ], vec![]).into()
             }
         ]);
     }
@@ -617,25 +868,139 @@ mod tests {
     #[test]
     fn uses() {
         assert_eq!(parse(r##"
-            use File from "@std/fs"
-            use File, Dir from "@std/fs"
+            import File from "@std/fs"
+            import File, Dir from "@std/fs"
         "##), vec![
             Statement::Use {
                 module: String::from("@std/fs"),
-                imports: vec![
-                    String::from("File"),
-                ]
+                imports: Imports::Named(vec![
+                    Import::new("File", None),
+                ])
             },
             Statement::Use {
                 module: String::from("@std/fs"),
-                imports: vec![
-                    String::from("File"),
-                    String::from("Dir"),
-                ]
+                imports: Imports::Named(vec![
+                    Import::new("File", None),
+                    Import::new("Dir", None),
+                ])
             }
         ]);
     }
 
+    #[test]
+    fn namespace_aliased_and_bare_uses() {
+        assert_eq!(parse(r##"
+            import * as http from "web/http"
+            import { Server as S, Token } from "web/http"
+            import File as F from "fs"
+            import "polyfill"
+        "##), vec![
+            Statement::Use {
+                module: String::from("web/http"),
+                imports: Imports::Namespace(String::from("http")),
+            },
+            Statement::Use {
+                module: String::from("web/http"),
+                imports: Imports::Named(vec![
+                    Import::new("Server", Some(String::from("S"))),
+                    Import::new("Token", None),
+                ])
+            },
+            Statement::Use {
+                module: String::from("fs"),
+                imports: Imports::Named(vec![
+                    Import::new("File", Some(String::from("F"))),
+                ])
+            },
+            Statement::Use {
+                module: String::from("polyfill"),
+                imports: Imports::Bare,
+            },
+        ]);
+
+        let errors = Parser::new(Lexer::new("import * from \"m\"\nimport a as from \"m\"")).parse().unwrap_err();
+
+        assert_eq!(errors.iter().map(|e| e.to_string()).collect::<Vec<String>>(), vec![
+            "expected `as` but found `from` on line 1, column 10",
+            "expected an identifier but found `from` on line 2, column 13",
+        ]);
+    }
+
+    #[test]
+    fn exports() {
+        assert_eq!(parse(r##"
+            export let a = 1
+            export fn b() {}
+            export { a, b, }
+        "##), vec![
+            Statement::Export {
+                declaration: Some(Box::new(Statement::Let {
+                    identifier: String::from("a"),
+                    r#type: None,
+                    initial: Expression::Number(1.0).into(),
+                    doc: None,
+                }.into())),
+                names: vec![],
+            },
+            Statement::Export {
+                declaration: Some(Box::new(Statement::Function {
+                    identifier: String::from("b"),
+                    parameters: vec![],
+                    return_type: None,
+                    body: vec![],
+                    doc: None,
+                }.into())),
+                names: vec![],
+            },
+            Statement::Export {
+                declaration: None,
+                names: vec![String::from("a"), String::from("b")],
+            },
+        ]);
+    }
+
+    #[test]
+    fn doc_comments() {
+        let program = parse(r##"
+            /// The answer.
+            let a = 42
+
+            /// Says hello.
+            /// Twice.
+            export fn hello() {
+                /// Not a declaration.
+                print("hello")
+            }
+
+            // Not a doc comment.
+            fn b() {}
+        "##);
+
+        let docs = program.iter().map(|statement| match &statement.kind {
+            Statement::Let { doc, .. } | Statement::Function { doc, .. } => doc.clone(),
+            Statement::Export { declaration: Some(declaration), .. } => match &declaration.kind {
+                Statement::Function { doc, .. } => doc.clone(),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }).collect::<Vec<Option<String>>>();
+
+        assert_eq!(docs, vec![Some("The answer.".to_owned()), Some("Says hello.\nTwice.".to_owned()), None]);
+    }
+
+    #[test]
+    fn rejects_nested_exports() {
+        let errors = Parser::new(Lexer::new("fn a() {
+    export let b = 1
+}
+export 1")).parse().unwrap_err();
+
+        assert_eq!(errors.iter().map(|e| e.to_string()).collect::<Vec<String>>(), vec![
+            "`export` is only allowed at the top level of a file on line 2, column 5",
+            "expected `fn`, `let` or `{` but found number `1` on line 4, column 8",
+        ]);
+    }
+
     #[test]
     fn maps() {
         assert_eq!(parse(r##"
@@ -645,8 +1010,8 @@ mod tests {
         "##), vec![
             Statement::Expression {
                 expression: Expression::Map(map!{
-                    String::from("foo") => Expression::String("bar".to_owned())
-                })
+                    String::from("foo") => Expression::String("bar".to_owned()).into()
+                }).into()
             }
         ])
     }
@@ -656,10 +1021,10 @@ mod tests {
         assert_eq!(parse("[1, 2, 3,]"), vec![
             Statement::Expression {
                 expression: Expression::Array(vec![
-                    Expression::Number(1.0),
-                    Expression::Number(2.0),
-                    Expression::Number(3.0),
-                ])
+                    Expression::Number(1.0).into(),
+                    Expression::Number(2.0).into(),
+                    Expression::Number(3.0).into(),
+                ]).into()
             }
         ]);
 
@@ -667,10 +1032,10 @@ mod tests {
             Statement::Expression {
                 expression: Expression::Index(
                     Expression::Array(vec![
-                        Expression::Number(1.0),
+                        Expression::Number(1.0).into(),
                     ]).boxed(),
                     Some(Expression::Number(0.0).boxed())
-                )
+                ).into()
             }
         ]);
     }
@@ -680,14 +1045,18 @@ mod tests {
         assert_eq!(parse("let name = 1"), vec![
             Statement::Let {
                 identifier: String::from("name"),
-                initial: Expression::Number(1.0),
+                r#type: None,
+                initial: Expression::Number(1.0).into(),
+                doc: None,
             },
         ]);
 
         assert_eq!(parse("let name: number = 1"), vec![
             Statement::Let {
                 identifier: String::from("name"),
-                initial: Expression::Number(1.0),
+                r#type: Some(Type::from(String::from("number"))),
+                initial: Expression::Number(1.0).into(),
+                doc: None,
             },
         ]);
     }
@@ -700,6 +1069,7 @@ mod tests {
                 parameters: Vec::new(),
                 return_type: None,
                 body: Vec::new(),
+                doc: None,
             }
         ]);
 
@@ -709,6 +1079,7 @@ mod tests {
                 parameters: Vec::new(),
                 return_type: Some(Type::from("Number".to_owned())),
                 body: Vec::new(),
+                doc: None,
             }
         ]);
 
@@ -720,6 +1091,7 @@ mod tests {
                 ],
                 return_type: None,
                 body: Vec::new(),
+                doc: None,
             }
         ]);
 
@@ -731,6 +1103,7 @@ mod tests {
                 ],
                 return_type: None,
                 body: Vec::new(),
+                doc: None,
             }
         ]);
 
@@ -742,6 +1115,7 @@ mod tests {
                 ],
                 return_type: Some(Type::from("String".to_owned())),
                 body: Vec::new(),
+                doc: None,
             }
         ]);
 
@@ -757,8 +1131,9 @@ mod tests {
                 ],
                 return_type: Some(Type::from("String".to_owned())),
                 body: vec![
-                    Statement::Let { identifier: String::from("name"), initial: Expression::String("testing".into()) },
+                    Statement::Let { identifier: String::from("name"), r#type: None, initial: Expression::String("testing".into()).into(), doc: None }.into(),
                 ],
+                doc: None,
             }
         ]);
     }
@@ -767,7 +1142,7 @@ mod tests {
     fn returns() {
         assert_eq!(parse("return true"), vec![
             Statement::Return {
-                expression: Expression::Bool(true),
+                expression: Expression::Bool(true).into(),
             }
         ]);
     }
@@ -776,7 +1151,7 @@ mod tests {
     fn if_statements() {
         assert_eq!(parse("if true {}"), vec![
             Statement::If {
-                condition: Expression::Bool(true),
+                condition: Expression::Bool(true).into(),
                 then: vec![],
                 otherwise: vec![],
             }
@@ -784,7 +1159,7 @@ mod tests {
 
         assert_eq!(parse("if true {} else {}"), vec![
             Statement::If {
-                condition: Expression::Bool(true),
+                condition: Expression::Bool(true).into(),
                 then: vec![],
                 otherwise: vec![],
             }
@@ -796,9 +1171,9 @@ mod tests {
             }
         "), vec![
             Statement::If {
-                condition: Expression::Bool(true),
+                condition: Expression::Bool(true).into(),
                 then: vec![
-                    Statement::Let { identifier: String::from("age"), initial: Expression::Number(1.0) }
+                    Statement::Let { identifier: String::from("age"), r#type: None, initial: Expression::Number(1.0).into(), doc: None }.into()
                 ],
                 otherwise: vec![],
             }
@@ -812,10 +1187,10 @@ mod tests {
             }
         "), vec![
             Statement::If {
-                condition: Expression::Bool(true),
+                condition: Expression::Bool(true).into(),
                 then: vec![],
                 otherwise: vec![
-                    Statement::Let { identifier: String::from("age"), initial: Expression::Number(1.0) }
+                    Statement::Let { identifier: String::from("age"), r#type: None, initial: Expression::Number(1.0).into(), doc: None }.into()
                 ],
             }
         ]);
@@ -824,12 +1199,12 @@ mod tests {
     #[test]
     fn while_statements() {
         assert_eq!(parse("while true {}"), vec![
-            Statement::While { condition: Expression::Bool(true), then: vec![] }
+            Statement::While { condition: Expression::Bool(true).into(), then: vec![] }
         ]);
 
         assert_eq!(parse("while true { 1 }"), vec![
-            Statement::While { condition: Expression::Bool(true), then: vec![
-                Statement::Expression { expression: Expression::Number(1.0) },
+            Statement::While { condition: Expression::Bool(true).into(), then: vec![
+                Statement::Expression { expression: Expression::Number(1.0).into() }.into(),
             ] }
         ]);
 
@@ -838,45 +1213,255 @@ mod tests {
                 break
             }
         "), vec![
-            Statement::While { condition: Expression::Bool(true), then: vec![
-                Statement::Break,
+            Statement::While { condition: Expression::Bool(true).into(), then: vec![
+                Statement::Break.into(),
             ] }
         ]);
 
         assert_eq!(parse("
             while true {
                 continue
-            }`
+            }
         "), vec![
-            Statement::While { condition: Expression::Bool(true), then: vec![
-                Statement::Continue,
+            Statement::While { condition: Expression::Bool(true).into(), then: vec![
+                Statement::Continue.into(),
             ] }
         ])
     }
 
+    #[test]
+    fn nested_while_statements() {
+        assert_eq!(parse("
+            while true {
+                while false {
+                    continue
+                }
+
+                break
+            }
+        "), vec![
+            Statement::While { condition: Expression::Bool(true).into(), then: vec![
+                Statement::While { condition: Expression::Bool(false).into(), then: vec![
+                    Statement::Continue.into(),
+                ] }.into(),
+                Statement::Break.into(),
+            ] }
+        ]);
+    }
+
+    #[test]
+    fn functions_reset_breakable_scope() {
+        let errors = Parser::new(Lexer::new("
+            while true {
+                let f = fn () {
+                    break
+                }
+
+                continue
+            }
+
+            fn g() {
+                continue
+            }
+        ")).parse().unwrap_err();
+
+        assert_eq!(errors.iter().map(|e| e.span.line).collect::<Vec<usize>>(), vec![4, 11]);
+        assert!(matches!(errors[0].err, ParserErrorType::InvalidBreakableScope));
+        assert!(matches!(errors[1].err, ParserErrorType::InvalidContinuableScope));
+    }
+
+    #[test]
+    fn lex_errors() {
+        let err = Parser::new(Lexer::new("let a = 1.2.3")).parse().unwrap_err().remove(0);
+
+        assert_eq!(err.span, Span::new(FileId(0), 8, 13, 1, 9));
+        assert!(matches!(err.err, ParserErrorType::Lex(LexErrorType::InvalidNumber(_))));
+
+        let err = Parser::new(Lexer::new("let a = \"hello")).parse().unwrap_err().remove(0);
+
+        assert!(matches!(err.err, ParserErrorType::Lex(LexErrorType::UnterminatedString)));
+    }
+
+    #[test]
+    fn spans() {
+        let program = Parser::new(Lexer::with_file("let a = (1 + b) * c\nwhile a {\n  f(a)[0]\n}", FileId(2))).parse().unwrap();
+
+        assert_eq!(program[0].span, Span::new(FileId(2), 0, 19, 1, 1));
+
+        let initial = match &program[0].kind {
+            Statement::Let { initial, .. } => initial,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(initial.span, Span::new(FileId(2), 8, 19, 1, 9));
+
+        let (lhs, rhs) = match &initial.kind {
+            Expression::Infix(lhs, Op::Multiply, rhs) => (lhs, rhs),
+            _ => unreachable!(),
+        };
+
+        // Parenthesized expressions include the parentheses.
+        assert_eq!(lhs.span, Span::new(FileId(2), 8, 15, 1, 9));
+        assert_eq!(rhs.span, Span::new(FileId(2), 18, 19, 1, 19));
+
+        assert_eq!(program[1].span, Span::new(FileId(2), 20, 41, 2, 1));
+
+        let (condition, then) = match &program[1].kind {
+            Statement::While { condition, then } => (condition, then),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(condition.span, Span::new(FileId(2), 26, 27, 2, 7));
+        assert_eq!(then[0].span, Span::new(FileId(2), 32, 39, 3, 3));
+
+        match &then[0].kind {
+            Statement::Expression { expression: Node { kind: Expression::Index(target, _), .. } } => {
+                assert_eq!(target.span, Span::new(FileId(2), 32, 36, 3, 3));
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn error_nodes_have_spans() {
+        let (program, _) = Parser::new(Lexer::new("let = 1\nlet a = 2")).parse_partial();
+
+        assert_eq!(program[0], Statement::Error);
+        assert_eq!(program[0].span, Span::new(FileId(0), 0, 7, 1, 1));
+        assert_eq!(program[1].span, Span::new(FileId(0), 8, 17, 2, 1));
+    }
+
+    #[test]
+    fn recovers_from_multiple_errors() {
+        let (program, errors) = Parser::new(Lexer::new("
+            let = 1
+            let a = 2
+            fn b( {
+                let c = 3
+            }
+            let d = )
+            let e = 5
+        ")).parse_partial();
+
+        // The `}` on line 6 no longer closes anything once the broken `fn` has been skipped.
+        assert_eq!(errors.len(), 4);
+        assert_eq!(errors.iter().map(|e| e.span.line).collect::<Vec<usize>>(), vec![2, 4, 6, 7]);
+
+        assert_eq!(program, vec![
+            Statement::Error,
+            Statement::Let { identifier: String::from("a"), r#type: None, initial: Expression::Number(2.0).into(), doc: None },
+            Statement::Error,
+            Statement::Let { identifier: String::from("c"), r#type: None, initial: Expression::Number(3.0).into(), doc: None },
+            Statement::Error,
+            Statement::Error,
+            Statement::Let { identifier: String::from("e"), r#type: None, initial: Expression::Number(5.0).into(), doc: None },
+        ]);
+    }
+
+    #[test]
+    fn recovers_inside_blocks() {
+        let (program, errors) = Parser::new(Lexer::new("
+            fn a() {
+                let = 1
+                let b = 2
+            }
+            let c = 3
+        ")).parse_partial();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(program, vec![
+            Statement::Function {
+                identifier: String::from("a"),
+                parameters: vec![],
+                return_type: None,
+                body: vec![
+                    Statement::Error.into(),
+                    Statement::Let { identifier: String::from("b"), r#type: None, initial: Expression::Number(2.0).into(), doc: None }.into(),
+                ],
+                doc: None,
+            },
+            Statement::Let { identifier: String::from("c"), r#type: None, initial: Expression::Number(3.0).into(), doc: None },
+        ]);
+    }
+
+    #[test]
+    fn reports_scope_errors_without_skipping() {
+        let errors = Parser::new(Lexer::new("
+            break
+            fn a() {
+                fn b() {}
+                continue
+            }
+            let c = [1, 2
+        ")).parse().unwrap_err();
+
+        assert!(matches!(errors[0].err, ParserErrorType::InvalidBreakableScope));
+        assert!(matches!(errors[1].err, ParserErrorType::NestedFunctionDefinition));
+        assert!(matches!(errors[2].err, ParserErrorType::InvalidContinuableScope));
+        assert!(matches!(errors[3].err, ParserErrorType::UnexpectedToken(..)));
+        assert_eq!(errors.len(), 4);
+    }
+
+    #[test]
+    fn recovers_from_unterminated_blocks_and_lex_errors() {
+        let (program, errors) = Parser::new(Lexer::new("let a = 1 # 2\nwhile true {")).parse_partial();
+
+        assert!(matches!(errors[0].err, ParserErrorType::Lex(LexErrorType::UnknownCharacter('#'))));
+        assert!(matches!(errors.last().unwrap().err, ParserErrorType::UnexpectedToken(..)));
+        assert_eq!(program[0], Statement::Let { identifier: String::from("a"), r#type: None, initial: Expression::Number(1.0).into(), doc: None });
+    }
+
+    #[test]
+    fn error_messages() {
+        let errors = Parser::new(Lexer::new("break\nlet a = (1 x)\nlet = 2\nlet b = ]")).parse().unwrap_err();
+
+        assert_eq!(errors.iter().map(|e| e.to_string()).collect::<Vec<String>>(), vec![
+            "`break` outside of a loop on line 1, column 1",
+            "expected `)` but found identifier `x` on line 2, column 12",
+            "expected an identifier but found `=` on line 3, column 5",
+            "expected an expression but found `]` on line 4, column 9",
+        ]);
+    }
+
     #[test]
     fn prefixes() {
         assert_eq!(parse("-1"), vec![
-            Statement::Expression { expression: Expression::Prefix(Op::Subtract, Box::new(Expression::Number(1.0))) },
+            Statement::Expression { expression: Expression::Prefix(Op::Subtract, Box::new(Expression::Number(1.0).into())).into() },
         ]);
     }
 
     #[test]
     fn infixes() {
         assert_eq!(parse("1 + 1"), vec![
-            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::Add, Expression::Number(1.0).boxed()) }
+            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::Add, Expression::Number(1.0).boxed()).into() }
         ]);
 
         assert_eq!(parse("1 - 1"), vec![
-            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::Subtract, Expression::Number(1.0).boxed()) }
+            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::Subtract, Expression::Number(1.0).boxed()).into() }
         ]);
 
         assert_eq!(parse("1 * 1"), vec![
-            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::Multiply, Expression::Number(1.0).boxed()) }
+            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::Multiply, Expression::Number(1.0).boxed()).into() }
         ]);
 
         assert_eq!(parse("1 / 1"), vec![
-            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::Divide, Expression::Number(1.0).boxed()) }
+            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::Divide, Expression::Number(1.0).boxed()).into() }
+        ]);
+
+        assert_eq!(parse("2 ** 3"), vec![
+            Statement::Expression { expression: Expression::Infix(Expression::Number(2.0).boxed(), Op::Power, Expression::Number(3.0).boxed()).into() }
+        ]);
+
+        assert_eq!(parse("2 ** 3 ** 2"), vec![
+            Statement::Expression {
+                expression: Expression::Infix(
+                    Expression::Number(2.0).boxed(), Op::Power, Expression::Infix(
+                        Expression::Number(3.0).boxed(),
+                        Op::Power,
+                        Expression::Number(2.0).boxed()
+                    ).boxed()
+                ).into()
+            }
         ]);
 
         assert_eq!(parse("1 + 2 * 3"), vec![
@@ -887,7 +1472,7 @@ mod tests {
                         Op::Multiply,
                         Expression::Number(3.0).boxed()
                     ).boxed()
-                )
+                ).into()
             }
         ]);
 
@@ -903,32 +1488,32 @@ mod tests {
                         Op::Divide,
                         Expression::Number(4.0).boxed()
                     ).boxed()
-                )
+                ).into()
             }
         ]);
 
         assert_eq!(parse("1 > 1"), vec![
-            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::GreaterThan, Expression::Number(1.0).boxed()) }
+            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::GreaterThan, Expression::Number(1.0).boxed()).into() }
         ]);
 
         assert_eq!(parse("1 < 1"), vec![
-            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::LessThan, Expression::Number(1.0).boxed()) }
+            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::LessThan, Expression::Number(1.0).boxed()).into() }
         ]);
 
         assert_eq!(parse("1 >= 1"), vec![
-            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::GreaterThanEquals, Expression::Number(1.0).boxed()) }
+            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::GreaterThanEquals, Expression::Number(1.0).boxed()).into() }
         ]);
 
         assert_eq!(parse("1 <= 1"), vec![
-            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::LessThanEquals, Expression::Number(1.0).boxed()) }
+            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::LessThanEquals, Expression::Number(1.0).boxed()).into() }
         ]);
 
         assert_eq!(parse("1 == 1"), vec![
-            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::Equals, Expression::Number(1.0).boxed()) }
+            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::Equals, Expression::Number(1.0).boxed()).into() }
         ]);
 
         assert_eq!(parse("1 != 1"), vec![
-            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::NotEquals, Expression::Number(1.0).boxed()) }
+            Statement::Expression { expression: Expression::Infix(Expression::Number(1.0).boxed(), Op::NotEquals, Expression::Number(1.0).boxed()).into() }
         ]);
         
         assert_eq!(parse("foo = 2"), vec![
@@ -936,7 +1521,7 @@ mod tests {
                 expression: Expression::Assign(
                     Expression::Identifier("foo".to_owned()).boxed(),
                     Expression::Number(2.0).boxed()
-                )
+                ).into()
             }
         ]);
     }
@@ -948,7 +1533,7 @@ mod tests {
                 expression: Expression::Call(
                     Expression::Identifier("foo".to_owned()).boxed(),
                     vec![]
-                )
+                ).into()
             }
         ]);
 
@@ -958,7 +1543,7 @@ mod tests {
                     Expression::Call(Expression::Identifier("foo".to_owned()).boxed(), Vec::new()).boxed(),
                     Op::Add,
                     Expression::Call(Expression::Identifier("foo".to_owned()).boxed(), Vec::new()).boxed(),
-                )
+                ).into()
             }
         ]);
 
@@ -967,9 +1552,9 @@ mod tests {
                 expression: Expression::Call(
                     Expression::Identifier("foo".to_owned()).boxed(),
                     vec![
-                        Expression::Number(1.0),
+                        Expression::Number(1.0).into(),
                     ]
-                )
+                ).into()
             }
         ]);
 
@@ -978,11 +1563,11 @@ mod tests {
                 expression: Expression::Call(
                     Expression::Identifier("foo".to_owned()).boxed(),
                     vec![
-                        Expression::Number(1.0),
-                        Expression::Number(2.0),
-                        Expression::Number(3.0),
+                        Expression::Number(1.0).into(),
+                        Expression::Number(2.0).into(),
+                        Expression::Number(3.0).into(),
                     ]
-                )
+                ).into()
             }
         ]);
 
@@ -991,11 +1576,11 @@ mod tests {
                 expression: Expression::Call(
                     Expression::Identifier("foo".to_owned()).boxed(),
                     vec![
-                        Expression::Number(1.0),
-                        Expression::Number(2.0),
-                        Expression::Number(3.0),
+                        Expression::Number(1.0).into(),
+                        Expression::Number(2.0).into(),
+                        Expression::Number(3.0).into(),
                     ]
-                )
+                ).into()
             }
         ]);
     }
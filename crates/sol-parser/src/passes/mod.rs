@@ -1,14 +1,53 @@
-use crate::Statement;
-use std::cmp::Ordering;
+use crate::{Program, Statement};
 
-pub fn pass(ast: &mut Vec<Statement>) {
+pub fn pass(ast: &mut Program) {
     hoist_functions(ast);
 }
 
-fn hoist_functions(ast: &mut Vec<Statement>) {
-    ast.sort_unstable_by(|a, _| if matches!(a, Statement::Function { .. }) {
-        Ordering::Less
-    } else {
-        Ordering::Equal
-    });
-}
\ No newline at end of file
+/// Move function declarations to the top of the program, keeping the order of everything else.
+///
+/// Exported functions are hoisted along with the rest, while `export let` and `export { .. }` stay where they
+/// are, since an export list can only refer to bindings that have been declared.
+fn hoist_functions(ast: &mut Program) {
+    let is_function = |statement: &Statement| match statement {
+        Statement::Function { .. } => true,
+        Statement::Export { declaration: Some(declaration), .. } => matches!(declaration.kind, Statement::Function { .. }),
+        _ => false,
+    };
+
+    // The sort is stable, so functions keep their order too.
+    ast.sort_by_key(|statement| ! is_function(&statement.kind));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, Statement};
+
+    fn kinds(source: &str) -> Vec<String> {
+        parse(source).unwrap().into_iter().map(|statement| match statement.kind {
+            Statement::Function { identifier, .. } => format!("fn {}", identifier),
+            Statement::Let { identifier, .. } => format!("let {}", identifier),
+            Statement::Export { declaration: Some(declaration), .. } => match declaration.kind {
+                Statement::Function { identifier, .. } => format!("export fn {}", identifier),
+                Statement::Let { identifier, .. } => format!("export let {}", identifier),
+                _ => unreachable!(),
+            },
+            Statement::Export { declaration: None, names } => format!("export {{ {} }}", names.join(", ")),
+            _ => "expression".to_owned(),
+        }).collect()
+    }
+
+    #[test]
+    fn hoists_functions_in_order() {
+        assert_eq!(kinds("let a = 1\nf()\nfn f() {}\nlet b = 2\nfn g() {}\nexport fn h() {}"), vec![
+            "fn f", "fn g", "export fn h", "let a", "expression", "let b",
+        ]);
+    }
+
+    #[test]
+    fn keeps_exports_after_declarations() {
+        assert_eq!(kinds("let a = 1\nexport { a }\nexport let b = 2\nfn f() {}"), vec![
+            "fn f", "let a", "export { a }", "export let b",
+        ]);
+    }
+}
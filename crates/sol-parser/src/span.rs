@@ -0,0 +1,33 @@
+/// Identifies the source file that a `Span` points into.
+/// 
+/// The parser doesn't know anything about files itself, it is up to the caller to hand out ids and
+/// keep track of which file each one belongs to.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct FileId(pub usize);
+
+/// Represents a region of source code.
+/// 
+/// `start` and `end` are byte offsets into the source, where `end` is exclusive. The `line` and `column`
+/// of the first character in the region are stored too, both starting at 1.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Span {
+    pub file: FileId,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(file: FileId, start: usize, end: usize, line: usize, column: usize) -> Self {
+        Self { file, start, end, line, column }
+    }
+
+    /// Create a `Span` that starts at `self` and finishes at the end of `other`.
+    pub fn to(self, other: Span) -> Self {
+        Self {
+            end: self.end.max(other.end),
+            ..self
+        }
+    }
+}
\ No newline at end of file
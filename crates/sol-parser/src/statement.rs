@@ -1,4 +1,4 @@
-use crate::{Expression, Type};
+use crate::{Expression, Node, Type};
 
 /// The main type of `Node` in Sol. Every line in the source code will eventually be parsed into
 /// a `Statement`, including arbitrary expressions.
@@ -9,35 +9,47 @@ use crate::{Expression, Type};
 pub enum Statement {
     Let {
         identifier: String,
-        initial: Expression,
+        r#type: Option<Type>,
+        initial: Node<Expression>,
+        /// The `///` comments directly before the declaration, without the slashes.
+        doc: Option<String>,
     },
     Function {
         identifier: String,
         parameters: Vec<Parameter>,
         return_type: Option<Type>,
-        body: Vec<Statement>,
+        body: Vec<Node<Statement>>,
+        doc: Option<String>,
     },
     If {
-        condition: Expression,
-        then: Vec<Statement>,
-        otherwise: Vec<Statement>,
+        condition: Node<Expression>,
+        then: Vec<Node<Statement>>,
+        otherwise: Vec<Node<Statement>>,
     },
     While {
-        condition: Expression,
-        then: Vec<Statement>,
+        condition: Node<Expression>,
+        then: Vec<Node<Statement>>,
     },
     Return {
-        expression: Expression,
+        expression: Node<Expression>,
     },
     Expression {
-        expression: Expression,
+        expression: Node<Expression>,
     },
     Use {
         module: String,
-        imports: Vec<String>,
+        imports: Imports,
+    },
+    /// `export fn` and `export let` hold the exported `declaration`, while `export { a, b }` only lists the
+    /// `names` of bindings that are declared elsewhere in the file.
+    Export {
+        declaration: Option<Box<Node<Statement>>>,
+        names: Vec<String>,
     },
     Break,
     Continue,
+    /// A placeholder for a statement that could not be parsed, produced when recovering from errors.
+    Error,
 }
 
 /// The `Parameter` struct is used to represent a function parameter.
@@ -53,4 +65,44 @@ impl Parameter {
     pub fn new(name: impl Into<String>, r#type: Option<Type>) -> Self {
         Self { name: name.into(), r#type }
     }
-}
\ No newline at end of file
+}
+
+/// What an `import` brings into scope from its module.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Imports {
+    /// `import a, b as c from "m"`, which can also be written as `import { a, b as c } from "m"`.
+    Named(Vec<Import>),
+    /// `import * as m from "m"`, where every export of the module is a member of `m`.
+    Namespace(String),
+    /// `import "m"`, which only runs the module for its side effects.
+    Bare,
+}
+
+impl Imports {
+    /// The names that the import declares in the importing file.
+    pub fn bindings(&self) -> Vec<&str> {
+        match self {
+            Imports::Named(imports) => imports.iter().map(Import::binding).collect(),
+            Imports::Namespace(name) => vec![name],
+            Imports::Bare => vec![],
+        }
+    }
+}
+
+/// A single export of a module that is imported by name, optionally under an `alias`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Import {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+impl Import {
+    pub fn new(name: impl Into<String>, alias: Option<String>) -> Self {
+        Self { name: name.into(), alias }
+    }
+
+    /// The name that the import is known by in the importing file.
+    pub fn binding(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+}
@@ -1,5 +1,5 @@
-/// A type-alias that represents the start and end point of a token.
-pub type Span = (usize, usize);
+use std::fmt::{Display, Formatter, Result};
+use crate::Span;
 
 /// Represents the "kind" of a token.
 /// 
@@ -16,6 +16,8 @@ pub enum TokenKind {
     Continue,
     Import,
     From,
+    As,
+    Export,
 
     True,
     False,
@@ -64,26 +66,87 @@ pub enum TokenKind {
     Eof,
 }
 
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let symbol = match self {
+            TokenKind::Identifier(i) => return write!(f, "identifier `{}`", i),
+            TokenKind::String(s) => return write!(f, "string {:?}", s),
+            TokenKind::Number(n) => return write!(f, "number `{}`", n),
+            TokenKind::Eof => return write!(f, "end of file"),
+            TokenKind::Fn => "fn",
+            TokenKind::Let => "let",
+            TokenKind::If => "if",
+            TokenKind::Else => "else",
+            TokenKind::While => "while",
+            TokenKind::Return => "return",
+            TokenKind::Break => "break",
+            TokenKind::Continue => "continue",
+            TokenKind::Import => "import",
+            TokenKind::From => "from",
+            TokenKind::As => "as",
+            TokenKind::Export => "export",
+            TokenKind::True => "true",
+            TokenKind::False => "false",
+            TokenKind::Colon => ":",
+            TokenKind::DoubleColon => "::",
+            TokenKind::SemiColon => ";",
+            TokenKind::Comma => ",",
+            TokenKind::Dot => ".",
+            TokenKind::Arrow => "->",
+            TokenKind::LeftParen => "(",
+            TokenKind::RightParen => ")",
+            TokenKind::LeftBrace => "{",
+            TokenKind::RightBrace => "}",
+            TokenKind::LeftBracket => "[",
+            TokenKind::RightBracket => "]",
+            TokenKind::Plus => "+",
+            TokenKind::Minus => "-",
+            TokenKind::Asterisk => "*",
+            TokenKind::Slash => "/",
+            TokenKind::Percent => "%",
+            TokenKind::DoubleAsterisk => "**",
+            TokenKind::And => "&&",
+            TokenKind::Or => "||",
+            TokenKind::Equals => "=",
+            TokenKind::PlusEquals => "+=",
+            TokenKind::MinusEquals => "-=",
+            TokenKind::AsteriskEquals => "*=",
+            TokenKind::SlashEquals => "/=",
+            TokenKind::EqualsEquals => "==",
+            TokenKind::NotEquals => "!=",
+            TokenKind::GreaterThan => ">",
+            TokenKind::GreaterThanEquals => ">=",
+            TokenKind::LessThan => "<",
+            TokenKind::LessThanEquals => "<=",
+            TokenKind::Not => "!",
+        };
+
+        write!(f, "`{}`", symbol)
+    }
+}
+
 /// Stores information regarding a token.
 /// 
-/// The `Token` type holds information about the type of a token (`TokenType`), as well as it's `line` and `span` (start and end column) in the source code.
+/// The `Token` type holds information about the type of a token (`TokenType`), as well as it's `span` in the source code.
+/// 
+/// Any `///` doc comments directly preceding the token are kept in `doc` as trivia.
 #[derive(Debug, Clone)]
 pub struct Token {
     pub kind: TokenKind,
-    pub line: usize,
     pub span: Span,
+    pub doc: Option<String>,
 }
 
 impl Token {
-    pub fn new(kind: TokenKind, line: usize, span: Span) -> Self {
-        Self { kind, line, span }
+    pub fn new(kind: TokenKind, span: Span) -> Self {
+        Self { kind, span, doc: None }
     }
 
     pub fn eof() -> Self {
         Self {
             kind: TokenKind::Eof,
-            line: 0,
-            span: (0, 0),
+            span: Span::default(),
+            doc: None,
         }
     }
 }
@@ -92,8 +155,8 @@ impl Default for Token {
     fn default() -> Self {
         Self {
             kind: TokenKind::Eof,
-            line: 0,
-            span: (0, 0),
+            span: Span::default(),
+            doc: None,
         }
     }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
 // js/web.js
-import { Server } from "@web/http";
+import { Server } from "web/http";
 
 // node_modules/regexparam/dist/regexparam.mjs
 function regexparam_default(str, loose) {
@@ -88,9 +88,29 @@ var Trouter = class {
 };
 
 // js/web.js
+var HttpError = class extends Error {
+  status;
+  constructor(status, message) {
+    super(message);
+    this.name = "HttpError";
+    this.status = status;
+  }
+};
 var Request = class {
+  method;
+  url;
+  path;
+  query;
+  headers;
+  body;
   params;
-  constructor(params) {
+  constructor(incoming, params) {
+    this.method = incoming.method;
+    this.url = incoming.url;
+    this.path = incoming.path;
+    this.query = incoming.query;
+    this.headers = incoming.headers;
+    this.body = incoming.body;
     this.params = params;
   }
   has(name) {
@@ -99,36 +119,154 @@ var Request = class {
   get(name) {
     return this.params[name];
   }
+  header(name) {
+    return this.headers[name.toLowerCase()];
+  }
+  json() {
+    try {
+      return JSON.parse(this.body);
+    } catch (error) {
+      throw new HttpError(400, "Bad Request");
+    }
+  }
+  form() {
+    let form = {};
+    let decode = (component) => {
+      try {
+        return decodeURIComponent(component.replace(/\+/g, " "));
+      } catch (error) {
+        throw new HttpError(400, "Bad Request");
+      }
+    };
+    for (let pair of this.body.split("&")) {
+      if (pair === "") {
+        continue;
+      }
+      let separator = pair.indexOf("=");
+      let [key, value] = separator === -1 ? [pair, ""] : [pair.slice(0, separator), pair.slice(separator + 1)];
+      form[decode(key)] = decode(value);
+    }
+    return form;
+  }
+};
+var Response = class {
+  code;
+  headers;
+  body;
+  constructor() {
+    this.code = 200;
+    this.headers = {};
+    this.body = void 0;
+  }
+  status(code) {
+    this.code = code;
+    return this;
+  }
+  header(name, value) {
+    this.headers[name] = value;
+    return this;
+  }
+  send(body) {
+    this.body = body;
+    return this;
+  }
+  json(value) {
+    return this.header("Content-Type", "application/json").send(JSON.stringify(value));
+  }
+  redirect(location, code = 302) {
+    return this.status(code).header("Location", location).send("");
+  }
 };
+var METHODS = ["GET", "HEAD", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"];
 var App = class {
   url;
   router;
+  logger;
   constructor(url) {
     this.url = url;
     this.router = new Trouter();
+    this.logger = println;
   }
   static init(url) {
     return new App(url);
   }
-  get(path, handler) {
-    this.router.get(path, handler);
+  use(path, handler) {
+    if (handler === void 0) {
+      this.router.use("/", path);
+    } else {
+      this.router.use(path, handler);
+    }
     return this;
   }
-  post(path, handler) {
-    this.router.post(path, handler);
+  get(path, ...handlers) {
+    this.router.get(path, ...handlers);
     return this;
   }
-  run() {
-    Server.init(this.url).serve((method, url) => {
-      println(url);
-      let handlers = this.router.find(method, url);
-      if (handlers.handlers.length < 1) {
-        return "404 Not Found.";
+  post(path, ...handlers) {
+    this.router.post(path, ...handlers);
+    return this;
+  }
+  put(path, ...handlers) {
+    this.router.put(path, ...handlers);
+    return this;
+  }
+  patch(path, ...handlers) {
+    this.router.patch(path, ...handlers);
+    return this;
+  }
+  delete(path, ...handlers) {
+    this.router.delete(path, ...handlers);
+    return this;
+  }
+  log(logger) {
+    this.logger = logger === true ? println : logger;
+    return this;
+  }
+  handle(incoming) {
+    let start = Date.now();
+    let { params, handlers } = this.router.find(incoming.method, incoming.path);
+    let request = new Request(incoming, params);
+    let response = new Response();
+    let run = (i) => {
+      if (i < handlers.length) {
+        return handlers[i](request, response, () => run(i + 1));
+      }
+      return this.missing(incoming.path, response);
+    };
+    try {
+      let result = run(0);
+      if (result !== void 0 && result !== response) {
+        response.send(result);
       }
-      let handler = handlers.handlers[0];
-      let request = new Request(handlers.params);
-      return handler(request);
-    });
+    } catch (error) {
+      response = new Response();
+      if (error instanceof HttpError) {
+        response.status(error.status).send(`${error.status} ${error.message}`);
+      } else {
+        eprintln(`Uncaught ${error} in the handler of ${incoming.method} ${incoming.url}`);
+        response.status(500).send("500 Internal Server Error");
+      }
+    }
+    if (this.logger) {
+      this.logger(`${incoming.method} ${incoming.url} ${response.code} ${Date.now() - start}ms`);
+    }
+    return {
+      status: response.code,
+      headers: response.headers,
+      body: response.body === void 0 ? "" : response.body
+    };
+  }
+  missing(path, response) {
+    let allowed = METHODS.filter((method) => this.router.routes.some((route) => {
+      return route.method === method && route.pattern.test(path);
+    }));
+    if (allowed.length > 0) {
+      return response.status(405).header("Allow", allowed.join(", ")).send("405 Method Not Allowed");
+    }
+    return response.status(404).send("404 Not Found");
+  }
+  run() {
+    Server.init(this.url).serve((incoming) => this.handle(incoming));
   }
 };
 export {
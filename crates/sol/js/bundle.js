@@ -0,0 +1,79 @@
+// The printing globals of Sol, for bundles that run in another JavaScript engine. They are set on `globalThis`
+// instead of declared in the bundle, so that a program can still define a function called e.g. `format`.
+if (typeof globalThis.println !== "function") {
+    /** Format a value the way it would be written in Sol, e.g. with strings in quotes. */
+    const inspect = (value, parents = []) => {
+        if (typeof value === "string") {
+            return JSON.stringify(value)
+        }
+
+        if (typeof value === "function") {
+            return value.name ? `[Function ${value.name}]` : "[Function (anonymous)]"
+        }
+
+        if (value === null || typeof value !== "object") {
+            return String(value)
+        }
+
+        if (parents.includes(value)) {
+            return "[Circular]"
+        }
+
+        if (value instanceof Error) {
+            return value.message ? `${value.name}: ${value.message}` : value.name
+        }
+
+        let nested = [...parents, value]
+
+        if (Array.isArray(value)) {
+            return `[${value.map((item) => inspect(item, nested)).join(", ")}]`
+        }
+
+        // Instances of classes are prefixed with the name of the class, plain objects (maps) aren't.
+        let name = value.constructor && value.constructor.name
+        let prefix = name && name !== "Object" ? `${name} ` : ""
+        let keys = Object.keys(value)
+
+        if (keys.length === 0) {
+            return `${prefix}{}`
+        }
+
+        let members = keys.map((key) => {
+            let written = /^[\p{L}_][\p{L}\p{N}_]*$/u.test(key) ? key : JSON.stringify(key)
+
+            return `${written}: ${inspect(value[key], nested)}`
+        })
+
+        return `${prefix}{ ${members.join(", ")} }`
+    }
+
+    const display = (value) => typeof value === "string" ? value : inspect(value)
+    const join = (values) => values.map(display).join(" ")
+
+    const write = typeof process === "object" && process.stdout
+        ? (text) => process.stdout.write(text)
+        : (text) => console.log(text)
+
+    Object.assign(globalThis, {
+        println: (...values) => console.log(join(values)),
+        print: (...values) => write(join(values)),
+        eprintln: (...values) => console.error(join(values)),
+        format: (template, ...values) => {
+            let placeholders = 0
+
+            return String(template).replace(/\{\{|\}\}|\{\}/g, (match) => {
+                if (match !== "{}") {
+                    return match[0]
+                }
+
+                placeholders += 1
+
+                if (values.length === 0) {
+                    throw new Error(`format: there is no value for placeholder ${placeholders} of the template`)
+                }
+
+                return display(values.shift())
+            })
+        },
+    })
+}
@@ -0,0 +1,92 @@
+import * as native from "fs/native"
+
+/**
+ * Errors thrown from Rust only have a message, which starts with the kind of the error, e.g.
+ * `NotFound: Could not read ...`. The kind is moved into its own `kind` property.
+ *
+ * @param f {Function}
+ */
+function wrap(f) {
+    return function (...args) {
+        try {
+            return f(...args)
+        } catch (error) {
+            const match = /^([A-Za-z]+): ([\s\S]*)$/.exec(error.message)
+
+            if (match !== null) {
+                error.kind = match[1]
+                error.message = match[2]
+            }
+
+            throw error
+        }
+    }
+}
+
+export const read = wrap(native.read)
+export const write = wrap(native.write)
+export const append = wrap(native.append)
+export const copy = wrap(native.copy)
+export const rename = wrap(native.rename)
+export const remove = wrap(native.remove)
+export const create_dir = wrap(native.create_dir)
+export const read_dir = wrap(native.read_dir)
+export const metadata = wrap(native.metadata)
+export const exists = native.exists
+export const glob = wrap(native.glob)
+export const temp_file = wrap(native.temp_file)
+export const temp_dir = wrap(native.temp_dir)
+
+/**
+ * Split text into lines, without the empty line after a trailing newline.
+ *
+ * @param text {string}
+ */
+function split(text) {
+    const lines = text.split(/\r?\n/)
+
+    if (lines[lines.length - 1] === "") {
+        lines.pop()
+    }
+
+    return lines
+}
+
+/** @param path {string} */
+export function lines(path) {
+    return split(read(path))
+}
+
+export class File {
+    /** @param path {string} */
+    constructor(path) {
+        this._path = path
+        this._contents = read(path.trim())
+    }
+
+    /** @param path {string} */
+    static read(path) {
+        return new File(path)
+    }
+
+    /** @param path {string} */
+    static exists(path) {
+        return exists(path)
+    }
+
+    path() {
+        return this._path
+    }
+
+    contents() {
+        return this._contents
+    }
+
+    lines() {
+        return split(this._contents)
+    }
+
+    is_empty() {
+        return this._contents.length === 0
+    }
+}
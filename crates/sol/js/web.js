@@ -1,10 +1,34 @@
-import { Server } from "@web/http"
+import { Server } from "web/http"
 import Trouter from 'trouter'
 
+/** An error that is answered with its status code instead of `500 Internal Server Error`. */
+class HttpError extends Error {
+    status;
+
+    constructor(status, message) {
+        super(message)
+
+        this.name = "HttpError"
+        this.status = status
+    }
+}
+
 class Request {
+    method;
+    url;
+    path;
+    query;
+    headers;
+    body;
     params;
 
-    constructor(params) {
+    constructor(incoming, params) {
+        this.method = incoming.method
+        this.url = incoming.url
+        this.path = incoming.path
+        this.query = incoming.query
+        this.headers = incoming.headers
+        this.body = incoming.body
         this.params = params
     }
 
@@ -15,46 +39,205 @@ class Request {
     get(name) {
         return this.params[name]
     }
+
+    header(name) {
+        return this.headers[name.toLowerCase()]
+    }
+
+    /** Parse the body as JSON. A body that isn't valid JSON is answered with `400 Bad Request`. */
+    json() {
+        try {
+            return JSON.parse(this.body)
+        } catch (error) {
+            throw new HttpError(400, "Bad Request")
+        }
+    }
+
+    /**
+     * Parse the body as a form, i.e. `application/x-www-form-urlencoded`. A body with an invalid escape, like
+     * `%zz`, is answered with `400 Bad Request`.
+     */
+    form() {
+        let form = {}
+        let decode = (component) => {
+            try {
+                return decodeURIComponent(component.replace(/\+/g, " "))
+            } catch (error) {
+                throw new HttpError(400, "Bad Request")
+            }
+        }
+
+        for (let pair of this.body.split("&")) {
+            if (pair === "") {
+                continue
+            }
+
+            // Only the first `=` separates the key from the value, which can contain more of them.
+            let separator = pair.indexOf("=")
+            let [key, value] = separator === -1 ? [pair, ""] : [pair.slice(0, separator), pair.slice(separator + 1)]
+
+            form[decode(key)] = decode(value)
+        }
+
+        return form
+    }
+}
+
+class Response {
+    code;
+    headers;
+    body;
+
+    constructor() {
+        this.code = 200
+        this.headers = {}
+        this.body = undefined
+    }
+
+    status(code) {
+        this.code = code
+        return this
+    }
+
+    header(name, value) {
+        this.headers[name] = value
+        return this
+    }
+
+    send(body) {
+        this.body = body
+        return this
+    }
+
+    json(value) {
+        return this.header("Content-Type", "application/json").send(JSON.stringify(value))
+    }
+
+    redirect(location, code = 302) {
+        return this.status(code).header("Location", location).send("")
+    }
 }
 
+const METHODS = ["GET", "HEAD", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"]
+
 export class App {
     url;
     router;
-    
+    logger;
+
     constructor(url) {
         this.url = url
         this.router = new Trouter
+        this.logger = println
     }
 
     static init(url) {
         return new App(url)
     }
 
-    get(path, handler) {
-        this.router.get(path, handler)
+    /** Run `handler` before the routes under `path` (or every route), as `handler(request, response, next)`. */
+    use(path, handler) {
+        if (handler === undefined) {
+            this.router.use("/", path)
+        } else {
+            this.router.use(path, handler)
+        }
+
         return this
     }
 
-    post(path, handler) {
-        this.router.post(path, handler)
+    get(path, ...handlers) {
+        this.router.get(path, ...handlers)
         return this
     }
 
-    run() {
-        Server.init(this.url)
-            .serve((method, url) => {
-                println(url)
-                
-                let handlers = this.router.find(method, url)
+    post(path, ...handlers) {
+        this.router.post(path, ...handlers)
+        return this
+    }
 
-                if (handlers.handlers.length < 1) {
-                    return "404 Not Found."
-                }
+    put(path, ...handlers) {
+        this.router.put(path, ...handlers)
+        return this
+    }
 
-                let handler = handlers.handlers[0];
-                let request = new Request(handlers.params)
+    patch(path, ...handlers) {
+        this.router.patch(path, ...handlers)
+        return this
+    }
+
+    delete(path, ...handlers) {
+        this.router.delete(path, ...handlers)
+        return this
+    }
 
-                return handler(request)
-            })
+    /** Log every request with `logger`, which is `println` by default, or turn logging off with `false`. */
+    log(logger) {
+        this.logger = logger === true ? println : logger
+        return this
     }
-}
\ No newline at end of file
+
+    /**
+     * Answer a request from `web/http` with a map of its `status`, `headers` and `body`. Handlers and middleware
+     * run in the order that they were added, until one of them doesn't call `next`.
+     */
+    handle(incoming) {
+        let start = Date.now()
+        let { params, handlers } = this.router.find(incoming.method, incoming.path)
+        let request = new Request(incoming, params)
+        let response = new Response()
+
+        let run = (i) => {
+            if (i < handlers.length) {
+                return handlers[i](request, response, () => run(i + 1))
+            }
+
+            return this.missing(incoming.path, response)
+        }
+
+        try {
+            let result = run(0)
+
+            if (result !== undefined && result !== response) {
+                response.send(result)
+            }
+        } catch (error) {
+            response = new Response()
+
+            if (error instanceof HttpError) {
+                response.status(error.status).send(`${error.status} ${error.message}`)
+            } else {
+                eprintln(`Uncaught ${error} in the handler of ${incoming.method} ${incoming.url}`)
+                response.status(500).send("500 Internal Server Error")
+            }
+        }
+
+        if (this.logger) {
+            this.logger(`${incoming.method} ${incoming.url} ${response.code} ${Date.now() - start}ms`)
+        }
+
+        return {
+            status: response.code,
+            headers: response.headers,
+            body: response.body === undefined ? "" : response.body,
+        }
+    }
+
+    /** Answer a request that no route matched: `405` if the path exists with other methods, `404` otherwise. */
+    missing(path, response) {
+        let allowed = METHODS.filter((method) => this.router.routes.some((route) => {
+            return route.method === method && route.pattern.test(path)
+        }))
+
+        if (allowed.length > 0) {
+            return response.status(405).header("Allow", allowed.join(", ")).send("405 Method Not Allowed")
+        }
+
+        return response.status(404).send("404 Not Found")
+    }
+
+    run() {
+        Server.init(this.url)
+            .serve((incoming) => this.handle(incoming))
+    }
+}
@@ -0,0 +1,208 @@
+use crate::loader::{self, display, location};
+use crate::{MODULES, POLYFILL};
+use sol_compiler::{parse, BundleModule, Diagnostic, Format, Link, Statement};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Defines the printing globals of Sol when the bundle runs in another JavaScript engine.
+const PRELUDE: &str = include_str!("../js/bundle.js");
+
+/// A bundle of a Sol file and every Sol file that it imports, directly or through other files.
+pub struct Bundle {
+    pub code: String,
+    /// The number of Sol files in the bundle.
+    pub modules: usize,
+    /// The modules that the bundle imports when it runs, like the built-in modules of Sol.
+    pub externals: Vec<String>,
+}
+
+/// Why a bundle couldn't be made.
+#[derive(Debug)]
+pub enum Error {
+    /// A file couldn't be read or found, or the imports are circular.
+    Message(String),
+    /// A file has syntax or compile errors, which are rendered with its source.
+    Diagnostics { file: String, source: String, diagnostics: Vec<Diagnostic> },
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Message(message)
+    }
+}
+
+/// The Sol files that are reachable from the entry point, with the entry point first.
+#[derive(Default)]
+struct Graph {
+    modules: Vec<BundleModule>,
+    sources: Vec<String>,
+    indices: HashMap<PathBuf, usize>,
+    externals: Vec<String>,
+}
+
+/// Bundle `entry` and the Sol files that it imports into a single JavaScript file, which runs without Sol.
+pub fn bundle(entry: &str, format: Format) -> Result<Bundle, Error> {
+    let entry = Path::new(entry).canonicalize().map_err(|e| format!("Could not read `{}`: {}", entry, e))?;
+
+    let mut graph = Graph::default();
+    graph.add(entry, &mut Vec::new())?;
+
+    let Graph { modules, sources, externals, .. } = graph;
+
+    let names = modules.iter().map(|module| module.name.clone()).collect::<Vec<String>>();
+    let count = modules.len();
+    let prelude = [POLYFILL, PRELUDE].join("\n");
+
+    let code = sol_compiler::bundle(modules, format, &prelude).map_err(|error| Error::Diagnostics {
+        file: names[error.module].clone(),
+        source: sources[error.module].clone(),
+        diagnostics: error.err.diagnostics(),
+    })?;
+
+    Ok(Bundle { code, modules: count, externals })
+}
+
+impl Graph {
+    /// Add the Sol file at `path` and the files that it imports, returning its index. `stack` holds the files
+    /// that are being added, to notice circular imports.
+    fn add(&mut self, path: PathBuf, stack: &mut Vec<PathBuf>) -> Result<usize, Error> {
+        if let Some(&i) = self.indices.get(&path) {
+            return Ok(i);
+        }
+
+        let name = display(&path);
+        let source = std::fs::read_to_string(&path).map_err(|e| format!("Could not read `{}`: {}", name, e))?;
+
+        let program = match parse(&source) {
+            Ok(program) => program,
+            Err(errors) => return Err(Error::Diagnostics { file: name, source, diagnostics: errors.iter().map(Diagnostic::from).collect() }),
+        };
+
+        let imports = program.iter().filter_map(|statement| match &statement.kind {
+            Statement::Use { module, .. } => Some(module.clone()),
+            _ => None,
+        }).collect::<Vec<String>>();
+
+        let i = self.modules.len();
+        let base = path.to_string_lossy().into_owned();
+
+        self.indices.insert(path.clone(), i);
+        self.modules.push(BundleModule { name, program, links: HashMap::new() });
+        self.sources.push(source);
+
+        stack.push(path);
+
+        for import in imports {
+            let file = if MODULES.contains(&import.as_str()) { None } else { loader::find(&base, &import)? };
+
+            let link = match file {
+                Some(file) => {
+                    let file = file.canonicalize().map_err(|e| format!("Could not read `{}`: {}", display(&file), e))?;
+
+                    if let Some(start) = stack.iter().position(|path| *path == file) {
+                        let cycle = stack[start..].iter().chain([&file]).map(|path| display(path)).collect::<Vec<String>>();
+
+                        return Err(Error::Message(format!("Circular import of `{}`{}: {}", import, location(&base, &import), cycle.join(" -> "))));
+                    }
+
+                    Link::Module(self.add(file, stack)?)
+                },
+                None => {
+                    if ! self.externals.contains(&import) {
+                        self.externals.push(import.clone());
+                    }
+
+                    Link::External
+                },
+            };
+
+            self.modules[i].links.insert(import, link);
+        }
+
+        stack.pop();
+
+        Ok(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::TempDir;
+    use rquickjs::{Context, Runtime};
+    use sol_compiler::Format;
+
+    /// A temporary directory with `files`, and the canonical path to it.
+    fn project(files: &[(&str, &str)]) -> (TempDir, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().canonicalize().unwrap();
+
+        for (name, source) in files {
+            std::fs::write(path.join(name), source).unwrap();
+        }
+
+        (dir, path)
+    }
+
+    /// What the bundle of `source` prints when it runs in an engine without the globals of Sol.
+    fn run(source: &str, format: Format) -> String {
+        let (_dir, path) = project(&[("main.sol", source)]);
+        let code = bundle(path.join("main.sol").to_str().unwrap(), format).unwrap().code;
+
+        let runtime = Runtime::new().unwrap();
+        let context = Context::full(&runtime).unwrap();
+
+        context.with(|ctx| {
+            ctx.eval::<(), _>(r#"globalThis.output = []; globalThis.console = { log: (text) => output.push(text) }"#).unwrap();
+
+            match format {
+                Format::Esm => ctx.compile("bundle.mjs", code.as_str()).map(|_| ()),
+                Format::Iife => ctx.eval::<(), _>(code.as_str()),
+            }.unwrap_or_else(|e| panic!("{}: {:?}", code, e));
+
+            ctx.eval::<String, _>(r#"output.join("\n")"#).unwrap()
+        })
+    }
+
+    #[test]
+    fn programs_can_define_helpers() {
+        let source = "fn format(value) {\n    return \"<\" + value + \">\"\n}\n\nprintln(format(\"a\"), [1])\n";
+
+        assert_eq!(run(source, Format::Esm), "<a> [1]");
+        assert_eq!(run(source, Format::Iife), "<a> [1]");
+    }
+
+    #[test]
+    fn returns_errors() {
+        let (_dir, path) = project(&[
+            ("main.sol", "import a from \"./a\"\n"),
+            ("a.sol", "export let a = (\n"),
+            ("b.sol", "import \"os/env\"\n"),
+            ("c.sol", "import d from \"./d\"\n"),
+            ("d.sol", "import c from \"./c\"\n"),
+        ]);
+        let file = |name: &str| path.join(name).to_string_lossy().into_owned();
+
+        match bundle(&file("main.sol"), Format::Esm) {
+            Err(Error::Diagnostics { file: name, source, diagnostics }) => {
+                assert_eq!((name, source.as_str()), (display(&path.join("a.sol")), "export let a = (\n"));
+                assert!(! diagnostics.is_empty());
+            },
+            result => panic!("expected diagnostics, got {:?}", result.map(|bundle| bundle.code)),
+        }
+
+        // Built-in modules can't be imported by a script.
+        match bundle(&file("b.sol"), Format::Iife) {
+            Err(Error::Diagnostics { file: name, diagnostics, .. }) => {
+                assert_eq!(name, display(&path.join("b.sol")));
+                assert!(diagnostics[0].message.contains("`os/env`"));
+            },
+            result => panic!("expected diagnostics, got {:?}", result.map(|bundle| bundle.code)),
+        }
+
+        match bundle(&file("c.sol"), Format::Esm) {
+            Err(Error::Message(message)) => assert!(message.starts_with("Circular import of `./c`"), "{}", message),
+            result => panic!("expected a message, got {:?}", result.map(|bundle| bundle.code)),
+        }
+    }
+}
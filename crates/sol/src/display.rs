@@ -0,0 +1,292 @@
+use crate::exception::exception;
+use rquickjs::{Coerced, Ctx, Func, Function, Object, Rest, Result, Type, Value};
+use std::io::Write;
+
+/// Define the printing globals: `println`, `print`, `eprintln` and `format`.
+pub fn register(ctx: Ctx) -> Result<()> {
+    let globals = ctx.globals();
+
+    globals.set("println", Func::from(println))?;
+    globals.set("print", Func::from(print))?;
+    globals.set("eprintln", Func::from(eprintln))?;
+    globals.set("format", Func::from(format))?;
+
+    Ok(())
+}
+
+/// Print the values separated by spaces, followed by a newline. Several values share one line, they aren't printed
+/// on a line each.
+fn println<'js>(ctx: Ctx<'js>, values: Rest<Value<'js>>) {
+    println!("{}", join(ctx, values));
+}
+
+/// Print the values separated by spaces, without a newline.
+fn print<'js>(ctx: Ctx<'js>, values: Rest<Value<'js>>) {
+    print!("{}", join(ctx, values));
+
+    let _ = std::io::stdout().flush();
+}
+
+/// Print the values separated by spaces to stderr, followed by a newline.
+fn eprintln<'js>(ctx: Ctx<'js>, values: Rest<Value<'js>>) {
+    eprintln!("{}", join(ctx, values));
+}
+
+/// Replace each `{}` in the template with the next value. `{{` and `}}` are written as `{` and `}`.
+///
+/// Values that are left over are ignored, but there has to be a value for every `{}`.
+fn format<'js>(ctx: Ctx<'js>, template: String, values: Rest<Value<'js>>) -> Result<String> {
+    let mut values = values.into_inner().into_iter();
+    let mut output = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    let mut placeholders = 0;
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                output.push(c);
+            },
+            ('{', Some('}')) => {
+                chars.next();
+                placeholders += 1;
+
+                match values.next() {
+                    Some(value) => output.push_str(&display(ctx, &value)),
+                    None => return Err(exception(format!("format: there is no value for placeholder {} of the template", placeholders))),
+                }
+            },
+            (c, _) => output.push(c),
+        }
+    }
+
+    Ok(output)
+}
+
+fn join<'js>(ctx: Ctx<'js>, values: Rest<Value<'js>>) -> String {
+    values.into_inner().iter().map(|v| display(ctx, v)).collect::<Vec<String>>().join(" ")
+}
+
+/// Format a value for printing. Strings are written as they are, everything else as `inspect` would.
+pub fn display<'js>(ctx: Ctx<'js>, value: &Value<'js>) -> String {
+    match value.as_string() {
+        Some(string) => string.to_string().unwrap_or_default(),
+        None => inspect(ctx, value),
+    }
+}
+
+/// Format a value the way it would be written in Sol, e.g. with strings in quotes.
+pub fn inspect<'js>(ctx: Ctx<'js>, value: &Value<'js>) -> String {
+    let mut output = String::new();
+
+    write(ctx, &mut output, value, &mut Vec::new());
+
+    output
+}
+
+/// Write `value` to `output`. `parents` holds the arrays and objects that are being written, so that a value
+/// containing itself is written as `[Circular]` instead of forever.
+fn write<'js>(ctx: Ctx<'js>, output: &mut String, value: &Value<'js>, parents: &mut Vec<Value<'js>>) {
+    match value.type_of() {
+        Type::String => output.push_str(&format!("{:?}", value.as_string().and_then(|s| s.to_string().ok()).unwrap_or_default())),
+        Type::Symbol => {
+            // Symbols can't be converted to a string implicitly, only through `String`.
+            let string = ctx.globals().get::<_, Function>("String").and_then(|f| f.call::<_, String>((value.clone(),)));
+
+            output.push_str(&string.unwrap_or_else(|_| "Symbol()".to_owned()));
+        },
+        Type::Function => {
+            let name = value.as_object().and_then(|f| f.get::<_, String>("name").ok()).unwrap_or_default();
+
+            if name.is_empty() {
+                output.push_str("[Function (anonymous)]");
+            } else {
+                output.push_str(&format!("[Function {}]", name));
+            }
+        },
+        Type::Array | Type::Object if parents.contains(value) => output.push_str("[Circular]"),
+        Type::Array => {
+            let array = value.as_array().unwrap();
+
+            parents.push(value.clone());
+            output.push('[');
+
+            for (i, item) in array.clone().into_iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+
+                match item {
+                    Ok(item) => write(ctx, output, &item, parents),
+                    Err(_) => output.push_str("undefined"),
+                }
+            }
+
+            output.push(']');
+            parents.pop();
+        },
+        Type::Object if value.is_error() => {
+            let error = value.as_object().unwrap();
+            let name = error.get::<_, String>("name").unwrap_or_else(|_| "Error".to_owned());
+            let message = error.get::<_, String>("message").unwrap_or_default();
+
+            if message.is_empty() {
+                output.push_str(&name);
+            } else {
+                output.push_str(&format!("{}: {}", name, message));
+            }
+        },
+        Type::Object => {
+            let object = value.as_object().unwrap();
+
+            // Instances of classes are prefixed with the name of the class, plain objects (maps) aren't.
+            let class = object.get::<_, Object>("constructor")
+                .and_then(|c| c.get::<_, String>("name"))
+                .ok()
+                .filter(|name| ! name.is_empty() && name != "Object");
+
+            if let Some(class) = class {
+                output.push_str(&class);
+                output.push(' ');
+            }
+
+            let keys = object.keys::<String>().filter_map(|key| key.ok()).collect::<Vec<String>>();
+
+            if keys.is_empty() {
+                output.push_str("{}");
+                return;
+            }
+
+            parents.push(value.clone());
+            output.push_str("{ ");
+
+            for (i, key) in keys.iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+
+                output.push_str(&key_name(key));
+                output.push_str(": ");
+
+                match object.get::<_, Value>(key.as_str()) {
+                    Ok(value) => write(ctx, output, &value, parents),
+                    Err(_) => output.push_str("undefined"),
+                }
+            }
+
+            output.push_str(" }");
+            parents.pop();
+        },
+        Type::Undefined | Type::Uninitialized => output.push_str("undefined"),
+        Type::Null => output.push_str("null"),
+        // Numbers are converted by JavaScript, so that e.g. `NaN` and `1e21` are written the same way.
+        _ => output.push_str(&value.get::<Coerced<String>>().map(|s| s.0).unwrap_or_default()),
+    }
+}
+
+/// Keys are written without quotes when they are valid identifiers.
+fn key_name(key: &str) -> String {
+    let is_identifier = key.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_alphanumeric() || c == '_');
+
+    if is_identifier {
+        key.to_owned()
+    } else {
+        format!("{:?}", key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rquickjs::{Context, Runtime};
+
+    /// Evaluate the JavaScript `code` and pass what it returns to `f`.
+    fn with_value(code: &str, f: impl for<'js> FnOnce(Ctx<'js>, Value<'js>)) {
+        let runtime = Runtime::new().unwrap();
+        let context = Context::full(&runtime).unwrap();
+
+        context.with(|ctx| f(ctx, ctx.eval(code).unwrap()));
+    }
+
+    fn inspected(code: &str) -> String {
+        let mut output = String::new();
+
+        with_value(code, |ctx, value| output = inspect(ctx, &value));
+
+        output
+    }
+
+    fn formatted(template: &str, values: &str) -> std::result::Result<String, String> {
+        let mut output = Ok(String::new());
+
+        with_value(values, |ctx, values| {
+            let values = values.into_array().unwrap().into_iter().collect::<Result<Vec<Value>>>().unwrap();
+
+            output = format(ctx, template.to_owned(), Rest(values)).map_err(|error| match error {
+                rquickjs::Error::Exception { message, .. } => message,
+                error => error.to_string(),
+            });
+        });
+
+        output
+    }
+
+    #[test]
+    fn inspects_values() {
+        assert_eq!(inspected(r#""a \"b\"""#), r#""a \"b\"""#);
+        assert_eq!(inspected("[1.5, NaN, true, null, undefined]"), "[1.5, NaN, true, null, undefined]");
+        assert_eq!(inspected("Symbol('s')"), "Symbol(s)");
+        assert_eq!(inspected("(function add() {})"), "[Function add]");
+        assert_eq!(inspected("() => {}"), "[Function (anonymous)]");
+        assert_eq!(inspected("new TypeError('bad')"), "TypeError: bad");
+        assert_eq!(inspected("({})"), "{}");
+    }
+
+    #[test]
+    fn inspects_nested_values() {
+        assert_eq!(
+            inspected(r#"[1, [2, { name: "a", "b c": [null, {}], nested: { list: [[]] } }]]"#),
+            r#"[1, [2, { name: "a", "b c": [null, {}], nested: { list: [[]] } }]]"#,
+        );
+        assert_eq!(inspected("new (class Point { constructor() { this.x = 1; this.y = [2] } })()"), "Point { x: 1, y: [2] }");
+        // The same value twice isn't a cycle.
+        assert_eq!(inspected("let a = [1]; [a, { a }]"), "[[1], { a: [1] }]");
+    }
+
+    #[test]
+    fn inspects_cycles() {
+        assert_eq!(inspected("let a = [1]; a.push(a); a"), "[1, [Circular]]");
+        assert_eq!(inspected("let o = { a: 1 }; o.self = o; o"), "{ a: 1, self: [Circular] }");
+        assert_eq!(inspected("let o = { list: [] }; o.list.push({ parent: o }); o"), "{ list: [{ parent: [Circular] }] }");
+    }
+
+    #[test]
+    fn displays_values() {
+        with_value(r#"["a", "b c", 1, ["d"]]"#, |ctx, values| {
+            let values = values.into_array().unwrap().into_iter().collect::<Result<Vec<Value>>>().unwrap();
+
+            // Only strings at the top are written without quotes.
+            assert_eq!(join(ctx, Rest(values)), r#"a b c 1 ["d"]"#);
+        });
+    }
+
+    #[test]
+    fn formats_templates() {
+        assert_eq!(formatted("{} + {} = {}", "[1, 2, 3]"), Ok("1 + 2 = 3".to_owned()));
+        assert_eq!(formatted("{} and {}", r#"["a", ["b"]]"#), Ok(r#"a and ["b"]"#.to_owned()));
+        assert_eq!(formatted("no placeholders", "[1]"), Ok("no placeholders".to_owned()));
+        // Values that are left over are ignored.
+        assert_eq!(formatted("{}", "[1, 2]"), Ok("1".to_owned()));
+        assert_eq!(formatted("{} {}", "[1]"), Err("format: there is no value for placeholder 2 of the template".to_owned()));
+    }
+
+    #[test]
+    fn formats_escapes() {
+        assert_eq!(formatted("{{}}", "[]"), Ok("{}".to_owned()));
+        assert_eq!(formatted("{{{}}}", "[1]"), Ok("{1}".to_owned()));
+        assert_eq!(formatted("{{ {} }}", "[1]"), Ok("{ 1 }".to_owned()));
+        // Single braces that aren't a placeholder are written as they are.
+        assert_eq!(formatted("} {", "[]"), Ok("} {".to_owned()));
+    }
+}
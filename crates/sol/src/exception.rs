@@ -0,0 +1,181 @@
+use rquickjs::{Coerced, Object, Value};
+use sol_compiler::SourceMap;
+use std::fmt::{Display, Formatter};
+
+/// An exception with `message` to throw in JavaScript, from the native functions of the built-in modules.
+pub fn exception(message: impl Into<String>) -> rquickjs::Error {
+    rquickjs::Error::Exception {
+        message: message.into(),
+        file: String::new(),
+        line: 0,
+        stack: String::new(),
+    }
+}
+
+/// A JavaScript exception that nothing caught, ready to be reported to the user.
+#[derive(Debug, Clone)]
+pub struct Exception {
+    pub name: String,
+    pub message: String,
+    pub stack: String,
+}
+
+impl Exception {
+    /// Read the exception from the value that was thrown, which doesn't have to be an `Error`.
+    pub fn from_value(value: Value) -> Self {
+        let string = |value: rquickjs::Result<Option<Coerced<String>>>| value.ok().flatten().map(|s| s.0).unwrap_or_default();
+
+        match value.get::<Object>() {
+            Ok(object) if value.is_error() => {
+                let name = string(object.get("name"));
+
+                Self {
+                    name: if name.is_empty() { "Error".to_owned() } else { name },
+                    message: string(object.get("message")),
+                    stack: string(object.get("stack")),
+                }
+            },
+            // Anything can be thrown in JavaScript, e.g. `throw "oops"`, so the value itself is the message.
+            _ => Self {
+                name: String::new(),
+                message: string(value.get()),
+                stack: String::new(),
+            },
+        }
+    }
+
+    /// Rewrite the positions in the stack trace so that they point at the Sol source code of `file`.
+    pub fn map_stack(&mut self, file: &str, source_map: &SourceMap, offset: usize) {
+        self.stack = map_stack(&self.stack, file, source_map, offset);
+    }
+}
+
+/// Errors from rquickjs itself only keep the message and stack of the exception.
+impl From<rquickjs::Error> for Exception {
+    fn from(error: rquickjs::Error) -> Self {
+        match error {
+            rquickjs::Error::Exception { message, stack, .. } => Self { name: "Error".to_owned(), message, stack },
+            error => Self { name: "InternalError".to_owned(), message: error.to_string(), stack: String::new() },
+        }
+    }
+}
+
+impl Display for Exception {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self.name.is_empty(), self.message.is_empty()) {
+            (true, _) => write!(f, "{}", self.message)?,
+            (false, true) => write!(f, "{}", self.name)?,
+            (false, false) => write!(f, "{}: {}", self.name, self.message)?,
+        }
+
+        for frame in self.stack.lines().filter(|l| ! l.trim().is_empty()) {
+            write!(f, "\n{}", frame)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Rewrite the positions in a QuickJS stack trace so that they point at the Sol source code of `file` instead
+/// of the generated JavaScript, which starts `offset` lines into the evaluated module.
+pub fn map_stack(stack: &str, file: &str, source_map: &SourceMap, offset: usize) -> String {
+    let location = format!("{}:", file);
+
+    stack.lines().map(|frame| {
+        // QuickJS formats frames as `at name (file:line)`, with lines starting at 1.
+        let start = match frame.find(&location) {
+            Some(start) => start + location.len(),
+            None => return frame.to_owned(),
+        };
+
+        let digits = frame[start..].chars().take_while(|c| c.is_ascii_digit()).count();
+        let line = match frame[start..start + digits].parse::<usize>() {
+            Ok(line) => line,
+            Err(_) => return frame.to_owned(),
+        };
+
+        // Frames inside of the polyfill point at the polyfill instead.
+        if line <= offset {
+            return format!("{}polyfill.js:{}", &frame[..start - location.len()], &frame[start..]);
+        }
+
+        match source_map.lookup(line - offset - 1, 0) {
+            Some(mapping) => format!("{}{}:{}{}", &frame[..start], mapping.source_line + 1, mapping.source_column + 1, &frame[start + digits..]),
+            None => frame.to_owned(),
+        }
+    }).collect::<Vec<String>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::POLYFILL;
+    use rquickjs::{Context, Runtime};
+    use sol_compiler::{compile_with_source_map, Mapping};
+
+    fn mapping(generated_line: usize, source_line: usize, source_column: usize) -> Mapping {
+        Mapping { generated_line, generated_column: 0, source_line, source_column }
+    }
+
+    /// The exception from the value that `code` throws.
+    fn thrown(code: &str) -> Exception {
+        let runtime = Runtime::new().unwrap();
+        let context = Context::full(&runtime).unwrap();
+
+        context.with(|ctx| Exception::from_value(ctx.eval(format!("try {{ {} }} catch (e) {{ e }}", code)).unwrap()))
+    }
+
+    #[test]
+    fn maps_frames() {
+        let source_map = SourceMap::new("main.sol", vec![mapping(0, 0, 0), mapping(2, 4, 4)]);
+        let stack = "    at f (main.sol:4)\n    at g (main.sol:6)\n    at <eval> (main.sol:2)\n    at h (other.sol:4)\n    at map (native)";
+
+        // The generated code starts after 2 lines, so `main.sol:4` is line 1 of the generated code.
+        assert_eq!(map_stack(stack, "main.sol", &source_map, 2), [
+            "    at f (main.sol:1:1)",
+            "    at g (main.sol:5:5)",
+            "    at <eval> (polyfill.js:2)",
+            "    at h (other.sol:4)",
+            "    at map (native)",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn keeps_frames_without_mappings() {
+        let source_map = SourceMap::new("main.sol", vec![mapping(3, 1, 0)]);
+
+        assert_eq!(map_stack("    at f (main.sol:2)", "main.sol", &source_map, 0), "    at f (main.sol:2)");
+        assert_eq!(map_stack("    at f (main.sol:x)", "main.sol", &source_map, 0), "    at f (main.sol:x)");
+        assert_eq!(map_stack("    at f (main.sol:5)", "main.sol", &source_map, 0), "    at f (main.sol:2:1)");
+    }
+
+    #[test]
+    fn maps_frames_after_the_polyfill() {
+        let source = "fn fail() {\n    let a = 1\n    return missing(a)\n}\n\nfail()\n";
+        let (compiled, source_map) = compile_with_source_map(source, "main.sol").unwrap();
+
+        let runtime = Runtime::new().unwrap();
+        let context = Context::full(&runtime).unwrap();
+
+        let mut exception = context.with(|ctx| {
+            Exception::from(ctx.compile("main.sol", [POLYFILL.to_owned(), compiled].join("\n")).map(|_| ()).unwrap_err())
+        });
+
+        exception.map_stack("main.sol", &source_map, POLYFILL.lines().count());
+
+        assert_eq!(exception.to_string(), "Error: 'missing' is not defined\n    at fail (main.sol:3:5)\n    at <anonymous> (main.sol:6:1)");
+    }
+
+    #[test]
+    fn reads_thrown_values() {
+        let error = thrown("throw new TypeError('bad')");
+        assert_eq!((error.name.as_str(), error.message.as_str()), ("TypeError", "bad"));
+        assert!(error.stack.contains("at <eval>"));
+
+        assert_eq!(thrown("throw new (class Oops extends Error {})()").to_string().lines().next(), Some("Error"));
+        assert_eq!(thrown("throw 1").to_string(), "1");
+        assert_eq!(thrown("throw 'x'").to_string(), "x");
+        assert_eq!(thrown("throw null").to_string(), "");
+        assert_eq!(thrown("throw { a: 1 }").to_string(), "[object Object]");
+    }
+}
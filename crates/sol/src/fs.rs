@@ -0,0 +1,292 @@
+use crate::exception::exception;
+use rquickjs::{Created, Ctx, Func, Loaded, Module, ModuleDef, Native, Object, Result};
+use std::fs::{self, Metadata, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// The native half of the `fs` module, which is imported by `js/fs.js` as `fs/native`.
+///
+/// Errors can only be thrown from Rust with a message, so every message starts with the kind of the error
+/// (e.g. `NotFound: ...`), which `js/fs.js` moves into the `kind` property of the error.
+pub struct Fs;
+
+const EXPORTS: &[&str] = &[
+    "read", "write", "append", "copy", "rename", "remove", "create_dir", "read_dir", "metadata", "exists", "glob",
+    "temp_file", "temp_dir",
+];
+
+impl ModuleDef for Fs {
+    fn load<'js>(_: Ctx<'js>, module: &Module<'js, Created>) -> Result<()> {
+        for export in EXPORTS {
+            module.add(*export)?;
+        }
+
+        Ok(())
+    }
+
+    fn eval<'js>(_: Ctx<'js>, module: &Module<'js, Loaded<Native>>) -> Result<()> {
+        module.set("read", Func::from(read))?;
+        module.set("write", Func::from(write))?;
+        module.set("append", Func::from(append))?;
+        module.set("copy", Func::from(copy))?;
+        module.set("rename", Func::from(rename))?;
+        module.set("remove", Func::from(remove))?;
+        module.set("create_dir", Func::from(create_dir))?;
+        module.set("read_dir", Func::from(read_dir))?;
+        module.set("metadata", Func::from(metadata))?;
+        module.set("exists", Func::from(exists))?;
+        module.set("glob", Func::from(glob))?;
+        module.set("temp_file", Func::from(temp_file))?;
+        module.set("temp_dir", Func::from(temp_dir))?;
+
+        Ok(())
+    }
+}
+
+/// Turn an I/O error into an exception whose message starts with the kind of the error.
+fn io_exception(error: io::Error, action: &str, path: &str) -> rquickjs::Error {
+    exception(format!("{:?}: Could not {} `{}`: {}", error.kind(), action, path, error))
+}
+
+/// Read the whole file as a string.
+fn read(path: String) -> Result<String> {
+    fs::read_to_string(&path).map_err(|e| io_exception(e, "read", &path))
+}
+
+/// Write `contents` to the file, creating it if needed and replacing whatever it contained.
+fn write(path: String, contents: String) -> Result<()> {
+    fs::write(&path, contents).map_err(|e| io_exception(e, "write to", &path))
+}
+
+/// Write `contents` to the end of the file, creating it if needed.
+fn append(path: String, contents: String) -> Result<()> {
+    OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|e| io_exception(e, "append to", &path))
+}
+
+/// Copy the file at `from` to `to`, replacing `to` if it exists.
+fn copy(from: String, to: String) -> Result<()> {
+    fs::copy(&from, &to).map(|_| ()).map_err(|e| {
+        // The error doesn't tell which of the paths failed, so it's `to` unless `from` isn't a file to copy.
+        match fs::metadata(&from) {
+            Ok(metadata) if metadata.is_file() => io_exception(e, "copy to", &to),
+            Ok(_) => io_exception(e, "copy", &from),
+            Err(source) => io_exception(source, "copy", &from),
+        }
+    })
+}
+
+/// Move a file or directory from `from` to `to`.
+fn rename(from: String, to: String) -> Result<()> {
+    fs::rename(&from, &to).map_err(|e| io_exception(e, "rename", &from))
+}
+
+/// Remove a file, or a directory together with everything inside of it.
+fn remove(path: String) -> Result<()> {
+    let result = match fs::symlink_metadata(&path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(&path),
+        Ok(_) => fs::remove_file(&path),
+        Err(e) => Err(e),
+    };
+
+    result.map_err(|e| io_exception(e, "remove", &path))
+}
+
+/// Create a directory and any of its parents that don't exist yet.
+fn create_dir(path: String) -> Result<()> {
+    fs::create_dir_all(&path).map_err(|e| io_exception(e, "create", &path))
+}
+
+/// List the entries of a directory, sorted by name, with the same members as `metadata` and their `name`.
+fn read_dir(ctx: Ctx, path: String) -> Result<Vec<Object>> {
+    let mut entries = fs::read_dir(&path)
+        .and_then(|entries| entries.collect::<io::Result<Vec<_>>>())
+        .map_err(|e| io_exception(e, "read", &path))?;
+
+    entries.sort_by_key(|entry| entry.file_name());
+
+    entries.into_iter().map(|entry| {
+        let path = entry.path().to_string_lossy().into_owned();
+        let metadata = entry.metadata().map_err(|e| io_exception(e, "read", &path))?;
+        let object = describe(ctx, &path, &metadata)?;
+
+        object.set("name", entry.file_name().to_string_lossy().into_owned())?;
+
+        Ok(object)
+    }).collect()
+}
+
+/// Describe a file or directory: its `path`, `size` in bytes, `modified` time in milliseconds since the Unix
+/// epoch, and whether it `is_dir`.
+fn metadata(ctx: Ctx, path: String) -> Result<Object> {
+    let metadata = fs::metadata(&path).map_err(|e| io_exception(e, "read", &path))?;
+
+    describe(ctx, &path, &metadata)
+}
+
+fn describe<'js>(ctx: Ctx<'js>, path: &str, metadata: &Metadata) -> Result<Object<'js>> {
+    // Not every platform records when a file was modified.
+    let modified = metadata.modified().ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as f64);
+
+    let object = Object::new(ctx)?;
+    object.set("path", path)?;
+    object.set("size", metadata.len() as f64)?;
+    object.set("modified", modified)?;
+    object.set("is_dir", metadata.is_dir())?;
+
+    Ok(object)
+}
+
+fn exists(path: String) -> bool {
+    Path::new(&path).exists()
+}
+
+/// List the paths that match a pattern like `src/**/*.sol`, in alphabetical order.
+fn glob(pattern: String) -> Result<Vec<String>> {
+    let paths = glob::glob(&pattern).map_err(|e| exception(format!("InvalidInput: `{}` is not a valid pattern: {}", pattern, e)))?;
+
+    paths
+        .map(|path| path.map(|p| p.to_string_lossy().into_owned()).map_err(|e| {
+            let path = e.path().to_string_lossy().into_owned();
+
+            io_exception(io::Error::from(e), "read", &path)
+        }))
+        .collect()
+}
+
+/// Create an empty file in the temporary directory of the system, and return its path.
+fn temp_file() -> Result<String> {
+    temporary(|path| OpenOptions::new().write(true).create_new(true).open(path).map(|_| ()))
+}
+
+/// Create an empty directory in the temporary directory of the system, and return its path.
+fn temp_dir() -> Result<String> {
+    temporary(|path| fs::create_dir(path))
+}
+
+/// Create something with a random name in the temporary directory, trying again if the name is taken.
+fn temporary(create: impl Fn(&Path) -> io::Result<()>) -> Result<String> {
+    loop {
+        let path = std::env::temp_dir().join(format!("sol-{:016x}", rand::random::<u64>()));
+
+        match create(&path) {
+            Ok(()) => return Ok(path.to_string_lossy().into_owned()),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(io_exception(e, "create", &path.to_string_lossy())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::package::TempDir;
+    use crate::run_module;
+
+    /// Run `code` with the `fs` module imported as `fs` and `dir` as the path of a temporary directory.
+    fn run(dir: &TempDir, code: &str) -> String {
+        run_module(&format!("import * as fs from \"fs\"\nconst dir = {:?}\n{}", dir.path().to_string_lossy(), code))
+    }
+
+    /// What calling `call` throws, as `kind: message`.
+    fn thrown(dir: &TempDir, call: &str) -> String {
+        run(dir, &format!("try {{ {}; globalThis.result = \"nothing\" }} catch (error) {{ globalThis.result = `${{error.kind}}: ${{error.message}}` }}", call))
+    }
+
+    #[test]
+    fn reads_and_writes() {
+        let dir = TempDir::new().unwrap();
+
+        assert_eq!(run(&dir, r#"
+            fs.write(dir + "/a.txt", "one\n")
+            fs.append(dir + "/a.txt", "two\n")
+            fs.copy(dir + "/a.txt", dir + "/b.txt")
+            fs.rename(dir + "/b.txt", dir + "/c.txt")
+            globalThis.result = JSON.stringify([fs.lines(dir + "/c.txt"), fs.exists(dir + "/b.txt"), fs.metadata(dir + "/a.txt").size])
+        "#), r#"[["one","two"],false,8]"#);
+    }
+
+    #[test]
+    fn throws_error_kinds() {
+        let dir = TempDir::new().unwrap();
+        let path = |name: &str| dir.path().join(name).to_string_lossy().into_owned();
+
+        assert_eq!(
+            thrown(&dir, r#"fs.read(dir + "/missing.txt")"#),
+            format!("NotFound: Could not read `{}`: No such file or directory (os error 2)", path("missing.txt")),
+        );
+        assert_eq!(
+            thrown(&dir, r#"fs.remove(dir + "/missing")"#),
+            format!("NotFound: Could not remove `{}`: No such file or directory (os error 2)", path("missing")),
+        );
+        assert!(thrown(&dir, r#"fs.glob("[")"#).starts_with("InvalidInput: `[` is not a valid pattern: "));
+        // Errors that aren't from the file system are left alone.
+        assert_eq!(thrown(&dir, "fs.read()"), "undefined: Error calling function with 0 argument(s) while 1..1 expected");
+    }
+
+    #[test]
+    fn reports_the_path_that_failed_to_copy() {
+        let dir = TempDir::new().unwrap();
+        let path = |name: &str| dir.path().join(name).to_string_lossy().into_owned();
+
+        std::fs::write(path("a.txt"), "a").unwrap();
+
+        assert_eq!(
+            thrown(&dir, r#"fs.copy(dir + "/missing.txt", dir + "/b.txt")"#),
+            format!("NotFound: Could not copy `{}`: No such file or directory (os error 2)", path("missing.txt")),
+        );
+        assert_eq!(
+            thrown(&dir, r#"fs.copy(dir + "/a.txt", dir + "/missing/b.txt")"#),
+            format!("NotFound: Could not copy to `{}`: No such file or directory (os error 2)", path("missing/b.txt")),
+        );
+        let directory = thrown(&dir, r#"fs.copy(dir, dir + "/b")"#);
+        assert!(directory.starts_with(&format!("InvalidInput: Could not copy `{}`: ", dir.path().display())), "{}", directory);
+    }
+
+    #[test]
+    fn reads_directories() {
+        let dir = TempDir::new().unwrap();
+
+        std::fs::create_dir_all(dir.path().join("src/lib")).unwrap();
+        std::fs::write(dir.path().join("src/main.sol"), "main").unwrap();
+        std::fs::write(dir.path().join("src/lib/b.sol"), "").unwrap();
+        std::fs::write(dir.path().join("src/lib/a.sol"), "").unwrap();
+        std::fs::write(dir.path().join("src/lib/c.js"), "").unwrap();
+
+        assert_eq!(run(&dir, r#"
+            globalThis.result = JSON.stringify(fs.read_dir(dir + "/src").map((entry) => [entry.name, entry.is_dir, entry.path === dir + "/src/" + entry.name]))
+        "#), r#"[["lib",true,true],["main.sol",false,true]]"#);
+        assert_eq!(run(&dir, r#"globalThis.result = fs.read_dir(dir + "/src")[1].size"#), "4");
+
+        assert_eq!(run(&dir, r#"
+            globalThis.result = JSON.stringify(fs.glob(dir + "/src/**/*.sol").map((path) => path.slice(dir.length)))
+        "#), r#"["/src/lib/a.sol","/src/lib/b.sol","/src/main.sol"]"#);
+
+        assert_eq!(thrown(&dir, r#"fs.read_dir(dir + "/src/main.sol")"#).split(':').next(), Some("NotADirectory"));
+    }
+
+    #[test]
+    fn creates_temporary_files() {
+        let dir = TempDir::new().unwrap();
+
+        let paths = run(&dir, r#"
+            let file = fs.temp_file()
+            let other = fs.temp_dir()
+            let created = [fs.metadata(file).is_dir, fs.metadata(file).size, fs.metadata(other).is_dir, file !== other]
+            globalThis.result = JSON.stringify({ created, paths: [file, other] })
+        "#);
+
+        assert!(paths.starts_with(r#"{"created":[false,0,true,true],"#), "{}", paths);
+
+        for path in paths.split('"').filter(|part| part.starts_with(&std::env::temp_dir().to_string_lossy().into_owned())) {
+            let _ = std::fs::remove_dir_all(path);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use crate::exception::{exception, Exception};
+use rquickjs::{Coerced, Created, Ctx, Func, Function, Loaded, Module, ModuleDef, Native, Object, Result, This, Type, Value};
+use tiny_http::{Header, Request, Response, StatusCode};
+
+/// The `web/http` module, a blocking HTTP server that passes every request to a handler written in Sol.
+///
+/// ```sol
+/// import Server from "web/http"
+///
+/// Server.init("127.0.0.1:8080").serve(fn (request) -> "Hello from " + request.path)
+/// ```
+pub struct Http;
+
+impl ModuleDef for Http {
+    fn load<'js>(_: Ctx<'js>, module: &Module<'js, Created>) -> Result<()> {
+        module.add("Server")
+    }
+
+    fn eval<'js>(ctx: Ctx<'js>, module: &Module<'js, Loaded<Native>>) -> Result<()> {
+        let server = Object::new(ctx)?;
+        server.set("init", Func::from(init))?;
+
+        module.set("Server", server)
+    }
+}
+
+/// Create a server for `address` (e.g. `0.0.0.0:8080`). Nothing is listening until `serve` is called.
+fn init(ctx: Ctx, address: String) -> Result<Object> {
+    let server = Object::new(ctx)?;
+    server.set("address", address)?;
+    server.set("serve", Func::from(serve))?;
+
+    Ok(server)
+}
+
+/// Listen on the address of the server and answer every request with what `handler` returns, forever.
+///
+/// The handler gets the request as a map with its `method`, `url`, `path`, `query`, `headers` and `body`. It can
+/// return a string, which is sent with status 200, or a map with a `status`, `headers` and `body`.
+fn serve<'js>(ctx: Ctx<'js>, this: This<Object<'js>>, handler: Function<'js>) -> Result<()> {
+    let address = this.0.get::<_, String>("address")?;
+    let server = tiny_http::Server::http(&address)
+        .map_err(|e| exception(format!("Could not listen on `{}`: {}", address, e)))?;
+
+    for mut request in server.incoming_requests() {
+        let description = format!("{} {}", request.method(), request.url());
+        let response = match to_js(ctx, &mut request) {
+            Ok(object) => match handler.call::<_, Value>((object,)) {
+                Ok(value) => from_js(value),
+                Err(error) => Err(error),
+            },
+            Err(error) => Err(error),
+        };
+
+        let response = response.unwrap_or_else(|error| {
+            // The server keeps running when a handler fails, so that one bad request doesn't take it down.
+            eprintln!("Uncaught {} in the handler of {}", Exception::from(error), description);
+
+            Response::from_string("500 Internal Server Error").with_status_code(500)
+        });
+
+        if let Err(e) = request.respond(response) {
+            eprintln!("Could not respond to {}: {}", description, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Turn the request into the map that is passed to the handler. Headers have lowercase names.
+fn to_js<'js>(ctx: Ctx<'js>, request: &mut Request) -> Result<Object<'js>> {
+    let mut body = String::new();
+    request.as_reader().read_to_string(&mut body)
+        .map_err(|e| exception(format!("Could not read the body of the request: {}", e)))?;
+
+    let url = request.url().to_owned();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+
+    let queries = Object::new(ctx)?;
+    for pair in query.split('&').filter(|pair| ! pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        queries.set(decode(key, true), decode(value, true))?;
+    }
+
+    let headers = Object::new(ctx)?;
+    for header in request.headers() {
+        let name = header.field.as_str().as_str().to_ascii_lowercase();
+
+        // Repeated headers are combined into one, the way HTTP allows.
+        let value = match headers.get::<_, Option<String>>(name.as_str())? {
+            Some(previous) => format!("{}, {}", previous, header.value),
+            None => header.value.to_string(),
+        };
+
+        headers.set(name, value)?;
+    }
+
+    let object = Object::new(ctx)?;
+    object.set("method", request.method().as_str())?;
+    object.set("url", url.as_str())?;
+    object.set("path", decode(path, false))?;
+    object.set("query", queries)?;
+    object.set("headers", headers)?;
+    object.set("body", body)?;
+
+    Ok(object)
+}
+
+/// Turn what the handler returned into a response.
+fn from_js(value: Value) -> Result<Response<std::io::Cursor<Vec<u8>>>> {
+    let object = match value.type_of() {
+        Type::Object => value.into_object().unwrap(),
+        Type::Undefined | Type::Null => return Ok(Response::from_string("").with_status_code(204)),
+        _ => return Ok(text(value.get::<Coerced<String>>()?.0)),
+    };
+
+    let body = match object.get::<_, Option<Coerced<String>>>("body")? {
+        Some(body) => body.0,
+        None => String::new(),
+    };
+
+    let mut response = text(body).with_status_code(StatusCode(object.get::<_, Option<u16>>("status")?.unwrap_or(200)));
+
+    if let Some(headers) = object.get::<_, Option<Object>>("headers")? {
+        for key in headers.keys::<String>() {
+            let key = key?;
+            let value = headers.get::<_, Coerced<String>>(key.as_str())?.0;
+            let header = Header::from_bytes(key.as_bytes(), value.as_bytes())
+                .map_err(|_| exception(format!("`{}: {}` is not a valid header", key, value)))?;
+
+            // A `Content-Type` replaces the one of `text` instead of being sent twice.
+            response.add_header(header);
+        }
+    }
+
+    Ok(response)
+}
+
+fn text(body: String) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_string(body)
+        .with_header(Header::from_bytes(&b"Content-Type"[..], &b"text/plain; charset=utf-8"[..]).unwrap())
+}
+
+/// Decode a component of a URL, where `%XX` is a byte. In the query string (`form`), `+` is a space too.
+fn decode(component: &str, form: bool) -> String {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            },
+            (b'+', _) if form => {
+                decoded.push(b' ');
+                i += 1;
+            },
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            },
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_components() {
+        assert_eq!(decode("a%20b%2Fc", false), "a b/c");
+        assert_eq!(decode("a+b%2Bc", true), "a b+c");
+        assert_eq!(decode("a+b", false), "a+b");
+        // Escapes that aren't followed by two hex digits are kept as they are.
+        assert_eq!(decode("100%", true), "100%");
+        assert_eq!(decode("%zz%4", true), "%zz%4");
+        assert_eq!(decode("caf%C3%A9", true), "café");
+    }
+
+    #[test]
+    fn overrides_the_content_type() {
+        let runtime = rquickjs::Runtime::new().unwrap();
+        let context = rquickjs::Context::full(&runtime).unwrap();
+
+        context.with(|ctx| {
+            let value = ctx.eval::<Value, _>(r#"({ status: 201, body: "{}", headers: { "Content-Type": "application/json", "X-Id": 1 } })"#).unwrap();
+            let response = from_js(value).unwrap();
+
+            let headers = response.headers().iter()
+                .map(|header| (header.field.as_str().as_str().to_owned(), header.value.as_str().to_owned()))
+                .filter(|(field, _)| field != "Date" && field != "Server")
+                .collect::<Vec<(String, String)>>();
+
+            assert_eq!(response.status_code(), StatusCode(201));
+            assert_eq!(headers, vec![
+                ("Content-Type".to_owned(), "application/json".to_owned()),
+                ("X-Id".to_owned(), "1".to_owned()),
+            ]);
+
+            let text = from_js(ctx.eval::<Value, _>("\"hello\"").unwrap()).unwrap();
+
+            assert_eq!(text.status_code(), StatusCode(200));
+            assert!(text.headers().iter().any(|header| header.value.as_str() == "text/plain; charset=utf-8"));
+        });
+    }
+}
@@ -0,0 +1,314 @@
+use crate::exception::exception;
+use crate::package::{self, Manifest, MODULES_DIR};
+use crate::POLYFILL;
+use rquickjs::{Ctx, Error, Loaded, Loader, Module, Resolver, Result};
+use sol_compiler::{compile, parse, Statement};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Resolves imports of other Sol files, e.g. `import add from "./math"`, to the absolute path of `math.sol` in
+/// the directory of the importing file.
+///
+/// Imports that don't start with `.` or `/` are packages installed in `sol_modules`, e.g. `import pad from
+/// "strings"` imports the entry point of `strings` and `import pad from "strings/pad"` imports its `pad.sol`.
+/// Built-in modules are resolved before this, and paths to JavaScript files are left to the `FileResolver`.
+#[derive(Default)]
+pub struct SolResolver {
+    /// The Sol files that every resolved Sol file imports, to notice circular imports.
+    imports: HashMap<String, Vec<String>>,
+}
+
+impl Resolver for SolResolver {
+    fn resolve<'js>(&mut self, _: Ctx<'js>, base: &str, name: &str) -> Result<String> {
+        let file = match find(base, name).map_err(exception)? {
+            Some(file) => file,
+            None => return Err(Error::new_resolving(base, name)),
+        };
+
+        let file = file.canonicalize()?.to_string_lossy().into_owned();
+
+        if let Some(mut cycle) = self.path(&file, base) {
+            cycle.push(file.as_str());
+
+            let cycle = cycle.iter().map(|module| display(Path::new(module))).collect::<Vec<String>>();
+
+            return Err(exception(format!("Circular import of `{}`{}: {}", name, location(base, name), cycle.join(" -> "))));
+        }
+
+        self.imports.entry(base.to_owned()).or_default().push(file.clone());
+
+        Ok(file)
+    }
+}
+
+impl SolResolver {
+    /// The modules on the way from `from` to `to` through their imports, if `from` imports `to` at all.
+    fn path<'a>(&'a self, from: &'a str, to: &str) -> Option<Vec<&'a str>> {
+        if from == to {
+            return Some(vec![from]);
+        }
+
+        self.imports.get(from)?.iter().find_map(|import| {
+            let mut path = self.path(import, to)?;
+            path.insert(0, from);
+
+            Some(path)
+        })
+    }
+}
+
+/// The Sol file that `base` imports as `name`, or `None` if it isn't a Sol file, e.g. a JavaScript file or a
+/// package that isn't in `sol_modules` or `sol.toml`, which is left to the other resolvers.
+pub fn find(base: &str, name: &str) -> std::result::Result<Option<PathBuf>, String> {
+    // The base isn't a path in the REPL, where imports are relative to the working directory.
+    let dir = match Path::new(base).parent() {
+        Some(dir) if dir.is_absolute() => dir.to_path_buf(),
+        _ => std::env::current_dir().unwrap_or_default(),
+    };
+
+    let (path, file) = if name.starts_with("./") || name.starts_with("../") || Path::new(name).is_absolute() {
+        let path = dir.join(name);
+        let file = if name.ends_with(".sol") { path.clone() } else { with_extension(&path, "sol") };
+
+        (path, file)
+    } else {
+        match package(&dir, base, name)? {
+            Some(found) => found,
+            None => return Ok(None),
+        }
+    };
+
+    if ! file.is_file() {
+        if path.is_file() || with_extension(&path, "js").is_file() {
+            return Ok(None);
+        }
+
+        return Err(format!("Cannot find module `{}`{}, there is no `{}`", name, location(base, name), display(&file)));
+    }
+
+    Ok(Some(file))
+}
+
+/// The path and the Sol file of a module in an installed package, e.g. `strings/pad` is `pad.sol` in
+/// `sol_modules/strings`, looking for `sol_modules` in `dir` and then in each of its parents.
+fn package(dir: &Path, base: &str, name: &str) -> std::result::Result<Option<(PathBuf, PathBuf)>, String> {
+    let (package, module) = match name.split_once('/') {
+        Some((package, module)) => (package, Some(module)),
+        None => (name, None),
+    };
+
+    let installed = dir.ancestors()
+        .map(|dir| dir.join(MODULES_DIR).join(package))
+        .find(|installed| installed.is_dir());
+
+    let installed = match installed {
+        Some(installed) => installed,
+        None => {
+            let dependency = package::root(dir)
+                .and_then(|root| Manifest::read(&root).ok())
+                .is_some_and(|manifest| manifest.dependencies.contains_key(package));
+
+            if dependency {
+                return Err(format!("Cannot find package `{}`{}, run `sol install` to install it", package, location(base, name)));
+            }
+
+            return Ok(None);
+        },
+    };
+
+    let module = match module {
+        Some(module) => module.to_owned(),
+        None => Manifest::read(&installed)?.package.entry,
+    };
+
+    let path = installed.join(module);
+    let file = if path.extension().is_some_and(|extension| extension == "sol") { path.clone() } else { with_extension(&path, "sol") };
+
+    Ok(Some((path, file)))
+}
+
+/// `path` with `extension` added to its file name, e.g. `./utils` becomes `./utils.sol`.
+fn with_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut file = path.as_os_str().to_owned();
+    file.push(".");
+    file.push(extension);
+
+    PathBuf::from(file)
+}
+
+/// The path relative to the working directory, if it's inside of it.
+pub fn display(path: &Path) -> String {
+    let cwd = std::env::current_dir().unwrap_or_default();
+
+    path.strip_prefix(&cwd).unwrap_or(path).to_string_lossy().into_owned()
+}
+
+/// Where `base` imports `name`, e.g. ` at main.sol:3:1`, or nothing if that can't be found.
+pub fn location(base: &str, name: &str) -> String {
+    let span = std::fs::read_to_string(base).ok()
+        .and_then(|source| parse(&source).ok())
+        .and_then(|program| program.into_iter().find_map(|statement| match statement.kind {
+            Statement::Use { module, .. } if module == name => Some(statement.span),
+            _ => None,
+        }));
+
+    match span {
+        Some(span) => format!(" at {}:{}:{}", display(Path::new(base)), span.line, span.column),
+        None => String::new(),
+    }
+}
+
+/// Loads the Sol files found by `SolResolver`, compiling them to JavaScript.
+///
+/// The compiled code is cached, so a file that hasn't changed isn't compiled again, e.g. after `:reset` in the
+/// REPL.
+#[derive(Default)]
+pub struct SolLoader {
+    /// The source and compiled code of every file that was loaded.
+    cache: HashMap<String, (String, String)>,
+}
+
+impl Loader for SolLoader {
+    fn load<'js>(&mut self, ctx: Ctx<'js>, name: &str) -> Result<Module<'js, Loaded>> {
+        if ! name.ends_with(".sol") {
+            return Err(Error::new_loading(name));
+        }
+
+        let source = std::fs::read_to_string(name)
+            .map_err(|e| exception(format!("Could not read `{}`: {}", display(Path::new(name)), e)))?;
+
+        let compiled = match self.cache.get(name) {
+            Some((cached, compiled)) if *cached == source => compiled.clone(),
+            _ => {
+                let compiled = compile(&source).map_err(|error| {
+                    let file = display(Path::new(name));
+                    let diagnostics = error.diagnostics().iter()
+                        .map(|diagnostic| diagnostic.render(&source, &file))
+                        .collect::<Vec<String>>();
+
+                    exception(format!("Could not compile `{}`:\n{}", file, diagnostics.join("\n")))
+                })?;
+
+                self.cache.insert(name.to_owned(), (source, compiled.clone()));
+
+                compiled
+            },
+        };
+
+        // Type checks call functions from the polyfill, which are only in scope in the module that defines them.
+        Module::new(ctx, name, [POLYFILL.to_owned(), compiled].join("\n")).map(|module| module.into_loaded())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::TempDir;
+    use rquickjs::{Context, Runtime};
+
+    /// A temporary directory with `files`, and the canonical path to it.
+    fn project(files: &[(&str, &str)]) -> (TempDir, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().canonicalize().unwrap();
+
+        for (name, source) in files {
+            std::fs::create_dir_all(path.join(name).parent().unwrap()).unwrap();
+            std::fs::write(path.join(name), source).unwrap();
+        }
+
+        (dir, path)
+    }
+
+    fn message(error: Error) -> String {
+        match error {
+            Error::Exception { message, .. } => message,
+            error => panic!("expected an exception, got {}", error),
+        }
+    }
+
+    #[test]
+    fn finds_relative_files() {
+        let (_dir, path) = project(&[
+            ("main.sol", ""),
+            ("lib/math.sol", ""),
+            ("lib/util.js", ""),
+        ]);
+        let base = path.join("main.sol").to_string_lossy().into_owned();
+        let lib = path.join("lib/math.sol").to_string_lossy().into_owned();
+
+        assert_eq!(find(&base, "./lib/math"), Ok(Some(path.join("./lib/math.sol"))));
+        assert_eq!(find(&base, "./lib/math.sol"), Ok(Some(path.join("./lib/math.sol"))));
+        assert_eq!(find(&lib, "../main"), Ok(Some(path.join("lib/../main.sol"))));
+        // JavaScript files and packages that aren't installed are left to the other resolvers.
+        assert_eq!(find(&base, "./lib/util"), Ok(None));
+        assert_eq!(find(&base, "./lib/util.js"), Ok(None));
+        assert_eq!(find(&base, "strings"), Ok(None));
+    }
+
+    #[test]
+    fn reports_missing_files() {
+        let (_dir, path) = project(&[("main.sol", "let a = 1\nimport add from \"./math\"\n")]);
+        let base = path.join("main.sol").to_string_lossy().into_owned();
+
+        assert_eq!(find(&base, "./math"), Err(format!(
+            "Cannot find module `./math` at {}:2:1, there is no `{}`",
+            display(&path.join("main.sol")),
+            display(&path.join("./math.sol")),
+        )));
+    }
+
+    #[test]
+    fn reports_circular_imports() {
+        let (_dir, path) = project(&[
+            ("a.sol", "import b from \"./b\"\n"),
+            ("b.sol", "import c from \"./c\"\n"),
+            ("c.sol", "\nimport a from \"./a\"\n"),
+        ]);
+        let file = |name: &str| path.join(name).to_string_lossy().into_owned();
+
+        let runtime = Runtime::new().unwrap();
+        let context = Context::full(&runtime).unwrap();
+        let mut resolver = SolResolver::default();
+
+        context.with(|ctx| {
+            assert_eq!(resolver.resolve(ctx, &file("a.sol"), "./b").unwrap(), file("b.sol"));
+            assert_eq!(resolver.resolve(ctx, &file("b.sol"), "./c").unwrap(), file("c.sol"));
+            // Importing the same module from somewhere else isn't a cycle.
+            assert_eq!(resolver.resolve(ctx, &file("a.sol"), "./c").unwrap(), file("c.sol"));
+
+            let error = resolver.resolve(ctx, &file("c.sol"), "./a").unwrap_err();
+
+            assert_eq!(message(error), format!(
+                "Circular import of `./a` at {}:2:1: {} -> {} -> {} -> {}",
+                display(Path::new(&file("c.sol"))),
+                display(Path::new(&file("a.sol"))),
+                display(Path::new(&file("b.sol"))),
+                display(Path::new(&file("c.sol"))),
+                display(Path::new(&file("a.sol"))),
+            ));
+        });
+    }
+
+    #[test]
+    fn reuses_compiled_files() {
+        let (_dir, path) = project(&[("main.sol", "export let a = 1\n")]);
+        let file = path.join("main.sol").to_string_lossy().into_owned();
+
+        let runtime = Runtime::new().unwrap();
+        let context = Context::full(&runtime).unwrap();
+        let mut loader = SolLoader::default();
+
+        context.with(|ctx| {
+            loader.load(ctx, &file).unwrap();
+
+            // Break the cached code, which is only used as long as the file hasn't changed.
+            loader.cache.get_mut(&file).unwrap().1 = "export let a = (".to_owned();
+            assert!(loader.load(ctx, &file).is_err());
+
+            std::fs::write(&file, "export let a = 2\n").unwrap();
+            loader.load(ctx, &file).unwrap();
+
+            assert!(loader.cache[&file].1.contains("export let a = 2;"));
+        });
+    }
+}
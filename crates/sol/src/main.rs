@@ -1,51 +1,135 @@
-extern crate three;
-
-use sol_compiler::compile;
-use rquickjs::{BuiltinLoader, BuiltinResolver, FileResolver, Runtime, ModuleLoader, ScriptLoader, Context, Func, Value, Rest, bind};
-use rustyline::{Editor, error::ReadlineError};
+use sol_compiler::{compile_with_source_map, format, lex, parse, Diagnostic, Format, Json, SourceMap};
+use std::path::PathBuf;
+use rquickjs::{BuiltinLoader, BuiltinResolver, FileResolver, Runtime, ModuleLoader, ScriptLoader, Context, Value, bind};
 use structopt::StructOpt;
+use exception::Exception;
+use fs::Fs;
+use http::Http;
+use loader::{SolLoader, SolResolver};
+use process::Process;
+
+mod bundle;
+mod display;
+mod exception;
+mod fs;
+mod http;
+mod loader;
+mod package;
+mod paths;
+mod process;
+mod repl;
 
 const VERSION: &str = "1.2.0";
 
 #[derive(Debug, StructOpt)]
+#[structopt(name = "sol", about = "The Sol Programming Language")]
 struct Cli {
-    #[structopt(long = "debug", short = "d", help = "Output debug information (JS, memory usage, etc)")]
-    debug: bool,
-
-    #[structopt(long = "raw", short = "r", help = "Execute the specified file as raw JavaScript")]
-    raw: bool,
-
     #[structopt(long = "version", short = "v", help = "Output the current version of Sol.")]
     version: bool,
 
-    file: Option<String>,
+    /// Starts the REPL when no command is given.
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    #[structopt(about = "Compile and run a Sol file", setting = structopt::clap::AppSettings::TrailingVarArg)]
+    Run {
+        #[structopt(long = "debug", short = "d", help = "Output debug information (JS, memory usage, etc)")]
+        debug: bool,
+
+        #[structopt(long = "raw", short = "r", help = "Execute the specified file as raw JavaScript")]
+        raw: bool,
+
+        #[structopt(help = "The file to run, which is the entry point in `sol.toml` by default")]
+        file: Option<String>,
+
+        #[structopt(help = "Arguments for the script, exported as `args` by `os/process`")]
+        args: Vec<String>,
+    },
+    #[structopt(about = "Compile a Sol file to JavaScript without running it")]
+    Build {
+        file: String,
+
+        #[structopt(long = "output", short = "o", help = "The file to write the JavaScript to, instead of stdout")]
+        output: Option<PathBuf>,
+
+        #[structopt(long = "source-map", help = "Also write a source map next to the output file")]
+        source_map: bool,
+    },
+    #[structopt(about = "Bundle a Sol file and the Sol files it imports into one JavaScript file")]
+    Bundle {
+        #[structopt(help = "The file to bundle, which is the entry point in `sol.toml` by default")]
+        file: Option<String>,
+
+        #[structopt(long = "output", short = "o", help = "The file to write the JavaScript to, instead of stdout")]
+        output: Option<PathBuf>,
+
+        #[structopt(long = "format", default_value = "esm", possible_values = &["esm", "iife"], help = "Write an ES module, or a script that runs everything in a function")]
+        format: String,
+    },
+    #[structopt(about = "Check a Sol file for errors without running it")]
+    Check {
+        file: String,
+    },
+    #[structopt(about = "Format a Sol file in place")]
+    Fmt {
+        file: String,
+
+        #[structopt(long = "check", help = "Exit with an error if the file isn't formatted, instead of formatting it")]
+        check: bool,
+    },
+    #[structopt(about = "Print the tokens of a Sol file as JSON")]
+    Lex {
+        file: String,
+    },
+    #[structopt(about = "Print the syntax tree of a Sol file as JSON")]
+    Ast {
+        file: String,
+    },
+    #[structopt(about = "Create a `sol.toml` for a project in the working directory")]
+    Init {
+        #[structopt(help = "The name of the project, which is the name of the directory by default")]
+        name: Option<String>,
+    },
+    #[structopt(about = "Add a package from a directory or a `.tar.gz` to the dependencies, and install it")]
+    Add {
+        source: String,
+    },
+    #[structopt(about = "Install the dependencies in `sol.toml` into `sol_modules`")]
+    Install,
 }
 
+/// The names of the built-in modules, which can be imported without a path.
+const MODULES: &[&str] = &[
+    // File system module
+    "fs",
+    // Operating System modules
+    "os/env",
+    "os/process",
+    // Path module
+    "path",
+    // Miscellaneous modules
+    "misc/token",
+    "misc/uuid",
+    // Web modules
+    "web/http",
+    "web/website",
+    "web/json",
+    // Math module
+    "math",
+];
+
 const POLYFILL: &str = include_str!("../js/polyfill.js");
 const WEB_MODULE: &str = include_str!("../dist/web.js");
 const JSON_MODULE: &str = include_str!("../js/json.js");
-
-pub fn println(vs: Rest<Value>) {
-    fn stringify(v: Value) -> String {
-        match true {
-            _ if v.is_string() => v.into_string().unwrap().to_string().unwrap(),
-            _ if v.is_number() => v.as_number().unwrap().to_string(),
-            _ if v.is_bool() => v.as_bool().unwrap().to_string(),
-            _ if v.is_array() => v.into_array().unwrap().into_iter().map(|v| stringify(v.unwrap())).collect::<Vec<String>>().join(", "),
-            _ => {
-                unimplemented!()
-            },
-        }
-    }
-
-    for v in vs.into_inner().into_iter() {
-        println!("{}", stringify(v));
-    }
-}
+const FS_MODULE: &str = include_str!("../js/fs.js");
 
 #[bind(module, public)]
 #[quickjs(bare)]
 mod token {
+    use crate::exception::exception;
     use sol_compiler::{lex, TokenKind};
 
     #[derive(Clone)]
@@ -61,30 +145,28 @@ mod token {
             }
         }
 
-        pub fn all(&self) -> Vec<(String, String)> {
-            let tokens = lex(&self.source[..]);
+        pub fn all(&self) -> rquickjs::Result<Vec<(String, String)>> {
+            let tokens = lex(&self.source[..]).map_err(|e| exception(e.to_string()))?;
             let mut js = Vec::new();
 
             for token in tokens {
+                // The name of the kind, without the value of literals, e.g. `Identifier` or `LeftParen`.
+                let debug = format!("{:?}", token.kind);
+                let name = debug.split('(').next().unwrap_or_default().to_owned();
+
                 js.push(match token.kind {
-                    TokenKind::Identifier(s) => (s, "Identifier".to_owned()),
-                    TokenKind::String(s) => (s, "String".to_owned()),
-                    TokenKind::Number(n) => (n.to_string(), "Number".to_owned()),
-                    TokenKind::Fn => ("fn".to_owned(), "Fn".to_owned()),
-                    TokenKind::LeftParen => ("(".to_string(), "LeftParen".to_owned()),
-                    TokenKind::RightParen => (")".to_string(), "RightParen".to_owned()),
-                    TokenKind::LeftBracket => ("[".to_string(), "LeftBracket".to_owned()),
-                    TokenKind::RightBracket => ("]".to_string(), "RightBracket".to_owned()),
-                    TokenKind::LeftBrace => ("{".to_string(), "LeftBrace".to_owned()),
-                    TokenKind::RightBrace => ("}".to_string(), "RightBrace".to_owned()),
-                    _ => unimplemented!("{:?}", token.kind)
+                    TokenKind::Identifier(s) | TokenKind::String(s) => (s, name),
+                    TokenKind::Number(n) => (n.to_string(), name),
+                    TokenKind::Eof => (String::new(), name),
+                    // Keywords and symbols are displayed in backticks, e.g. `fn` or `(`.
+                    kind => (kind.to_string().trim_matches('`').to_owned(), name),
                 });
             }
 
-            js
+            Ok(js)
         }
 
-        pub fn tokenize(source: String) -> Vec<(String, String)> {
+        pub fn tokenize(source: String) -> rquickjs::Result<Vec<(String, String)>> {
             let this = Self::new(source);
 
             this.all()
@@ -92,51 +174,6 @@ mod token {
     }
 }
 
-#[bind(module, public)]
-#[quickjs(bare)]
-mod fs {
-    #[derive(Clone)]
-    #[quickjs(cloneable)]
-    pub struct File {
-        path: String,
-        contents: String,
-    }
-
-    impl File {
-        pub fn new(path: String) -> Self {
-            // TODO: Check the file exists before trying to read it.
-            Self {
-                path: path.clone(),
-                contents: std::fs::read_to_string(path.trim()).unwrap(),
-            }
-        }
-
-        pub fn path(&self) -> String {
-            self.path.clone()
-        }
-
-        pub fn lines(&self) -> Vec<&str> {
-            self.contents.lines().collect()
-        }
-
-        pub fn is_empty(&self) -> bool {
-            self.contents.is_empty()
-        }
-
-        pub fn exists(path: String) -> bool {
-            std::fs::metadata(path).is_ok()
-        }
-
-        pub fn contents(&self) -> String {
-            self.contents.clone()
-        }
-
-        pub fn read(path: String) -> Self {
-            Self::new(path)
-        }
-    }
-}
-
 #[bind(module, public)]
 #[quickjs(bare)]
 mod math {
@@ -343,6 +380,65 @@ mod env {
     }
 }
 
+#[bind(module, public)]
+#[quickjs(bare)]
+mod path {
+    use crate::exception::exception;
+    use crate::paths;
+    use rquickjs::Rest;
+    use std::path::{Path, PathBuf};
+
+    fn string(path: &Path) -> String {
+        path.to_string_lossy().into_owned()
+    }
+
+    /// Join the parts with the separator of the platform. An absolute part replaces everything before it.
+    pub fn join(parts: Rest<String>) -> String {
+        string(&parts.into_inner().iter().collect::<PathBuf>())
+    }
+
+    /// Resolve `.` and `..` in the path. An empty result is written as `.`.
+    pub fn normalize(path: String) -> String {
+        let normalized = string(&paths::lexical(Path::new(&path)));
+
+        if normalized.is_empty() { ".".to_owned() } else { normalized }
+    }
+
+    /// The path without its last component, or an empty string if it doesn't have one.
+    pub fn dirname(path: String) -> String {
+        Path::new(&path).parent().map(string).unwrap_or_default()
+    }
+
+    /// The last component of the path, or an empty string if it doesn't have one (e.g. `/` or `..`).
+    pub fn basename(path: String) -> String {
+        Path::new(&path).file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+
+    /// The extension of the last component without the `.`, or an empty string if it doesn't have one.
+    pub fn extension(path: String) -> String {
+        Path::new(&path).extension().map(|extension| extension.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+
+    /// The last component without its extension.
+    pub fn stem(path: String) -> String {
+        Path::new(&path).file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+
+    pub fn is_absolute(path: String) -> bool {
+        Path::new(&path).is_absolute()
+    }
+
+    /// The path that leads from `from` to `to`. Relative paths are resolved against the working directory first.
+    pub fn relative(from: String, to: String) -> String {
+        string(&paths::relative(Path::new(&from), Path::new(&to)))
+    }
+
+    /// The absolute path with every symbolic link resolved. The path has to exist.
+    pub fn canonicalize(path: String) -> rquickjs::Result<String> {
+        std::fs::canonicalize(&path).map(|path| string(&path)).map_err(|e| exception(format!("Could not canonicalize `{}`: {}", path, e)))
+    }
+}
+
 #[bind(module, public)]
 #[quickjs(bare)]
 mod uuid {
@@ -380,38 +476,236 @@ fn main() {
         std::process::exit(0);
     }
 
+    match args.command {
+        Some(Command::Run { debug, raw, file, args }) => run(file.unwrap_or_else(entry), args, raw, debug),
+        Some(Command::Build { file, output, source_map }) => build(file, output, source_map),
+        Some(Command::Bundle { file, output, format }) => bundle(file.unwrap_or_else(entry), output, &format),
+        Some(Command::Check { file }) => {
+            compile_or_exit(&read(&file), &file);
+        },
+        Some(Command::Fmt { file, check }) => fmt(file, check),
+        Some(Command::Lex { file }) => {
+            let source = read(&file);
+
+            match lex(&source) {
+                Ok(tokens) => println!("{:#}", Json::from(tokens.as_slice())),
+                Err(e) => exit_with_diagnostics(&source, &file, vec![Diagnostic::from(&e)]),
+            }
+        },
+        Some(Command::Ast { file }) => {
+            let source = read(&file);
+
+            match parse(&source) {
+                Ok(program) => println!("{:#}", Json::from(&program)),
+                Err(errors) => exit_with_diagnostics(&source, &file, errors.iter().map(Diagnostic::from).collect()),
+            }
+        },
+        Some(Command::Init { name }) => {
+            let cwd = std::env::current_dir().unwrap();
+
+            exit_on_error(package::init(&cwd, name));
+        },
+        Some(Command::Add { source }) => exit_on_error(package::add(&project(), &source)),
+        Some(Command::Install) => exit_on_error(package::install(&project())),
+        None => {
+            let runtime = runtime(None);
+            let context = Context::full(&runtime).unwrap();
+
+            repl::start(&runtime, context);
+        },
+    }
+}
+
+fn run(file: String, args: Vec<String>, raw: bool, debug: bool) {
+    let contents = read(&file);
+    let (compiled, source_map) = if raw {
+        (contents, None)
+    } else {
+        let (compiled, source_map) = compile_or_exit(&contents[..], &file);
+
+        (compiled, Some(source_map))
+    };
+
+    let path = std::fs::canonicalize(file).unwrap();
+    let name = path.to_string_lossy();
+    let dir = path.parent().unwrap();
+
+    if debug {
+        println!("=== JS OUTPUT ===");
+        println!("{}", compiled);
+    }
+
+    process::set_args(args);
+
+    let runtime = runtime(Some((&name, [POLYFILL.to_string(), compiled].join("\n"))));
+    let context = Context::full(&runtime).unwrap();
+
+    context.with(|ctx: rquickjs::Ctx| {
+        let glob = ctx.globals();
+
+        // Printing to le console
+        display::register(ctx).unwrap();
+        // File system
+        glob.set("__FILE__", path.to_str()).unwrap();
+        glob.set("__DIR__", dir.to_str()).unwrap();
+
+        if debug {
+            println!("=== EVAL ===");
+        }
+
+        // The script is loaded through a dynamic import, so that the thrown value can be caught in JavaScript.
+        // Once an exception reaches Rust, only its message and stack trace are left.
+        ctx.eval::<(), _>(format!("import({:?}).catch(e => {{ globalThis.__sol_uncaught = e }})", name)).unwrap();
+    });
+
+    let exception = loop {
+        match runtime.execute_pending_job() {
+            Ok(true) => continue,
+            Ok(false) => break None,
+            Err(e) => break Some(Exception::from(e)),
+        }
+    };
+
+    let exception = exception.or_else(|| context.with(|ctx: rquickjs::Ctx| {
+        let value = ctx.globals().get::<_, Value>("__sol_uncaught").ok()?;
+
+        if value.type_of().is_void() {
+            None
+        } else {
+            Some(Exception::from_value(value))
+        }
+    }));
+
+    if let Some(mut exception) = exception {
+        if let Some(source_map) = &source_map {
+            // The compiled code comes after the polyfill, so its lines are offset by the polyfill's.
+            exception.map_stack(&name, source_map, POLYFILL.lines().count());
+        }
+
+        eprintln!("Uncaught {}", exception);
+
+        std::process::exit(1);
+    }
+
+    if debug {
+        println!("=== DEBUG ===");
+        println!("Memory used (bytes): {}", runtime.memory_usage().memory_used_size);
+    }
+}
+
+/// Write the compiled JavaScript, with the polyfill that it depends on, so that it can run without Sol.
+fn build(file: String, output: Option<PathBuf>, source_map: bool) {
+    let (compiled, mut map) = compile_or_exit(&read(&file), &file);
+    let mut js = [POLYFILL, &compiled].join("\n");
+
+    let output = match output {
+        Some(output) => output,
+        None => {
+            print!("{}", js);
+            return;
+        },
+    };
+
+    if source_map {
+        let map_path = PathBuf::from(format!("{}.map", output.display()));
+        let offset = POLYFILL.lines().count();
+
+        for mapping in map.mappings.iter_mut() {
+            mapping.generated_line += offset;
+        }
+
+        if let Some(name) = output.file_name() {
+            map = map.file(name.to_string_lossy());
+        }
+
+        write(&map_path, &map.to_json());
+
+        if ! js.ends_with('\n') {
+            js.push('\n');
+        }
+
+        js.push_str(&format!("//# sourceMappingURL={}\n", map_path.file_name().unwrap().to_string_lossy()));
+    }
+
+    write(&output, &js);
+}
+
+/// Write the bundle of `file`, reporting the modules that it still imports when it runs.
+fn bundle(file: String, output: Option<PathBuf>, format: &str) {
+    let format = if format == "iife" { Format::Iife } else { Format::Esm };
+
+    let bundle = match bundle::bundle(&file, format) {
+        Ok(bundle) => bundle,
+        Err(bundle::Error::Diagnostics { file, source, diagnostics }) => exit_with_diagnostics(&source, &file, diagnostics),
+        Err(bundle::Error::Message(e)) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        },
+    };
+
+    match &output {
+        Some(output) => {
+            write(output, &bundle.code);
+            eprintln!("Bundled {} module{} into `{}`.", bundle.modules, if bundle.modules == 1 { "" } else { "s" }, output.display());
+        },
+        None => print!("{}", bundle.code),
+    }
+
+    if ! bundle.externals.is_empty() {
+        eprintln!("External modules, which are imported when the bundle runs: {}", bundle.externals.join(", "));
+    }
+}
+
+fn fmt(file: String, check: bool) {
+    let source = read(&file);
+
+    let formatted = match format(&source) {
+        Ok(formatted) => formatted,
+        Err(errors) => exit_with_diagnostics(&source, &file, errors.iter().map(Diagnostic::from).collect()),
+    };
+
+    if formatted == source {
+        return;
+    }
+
+    if check {
+        eprintln!("`{}` is not formatted.", file);
+
+        std::process::exit(1);
+    }
+
+    write(file.as_ref(), &formatted);
+}
+
+/// Create the runtime, with every built-in module and the compiled `script` (if there is one) registered.
+fn runtime(script: Option<(&str, String)>) -> Runtime {
     let runtime: Runtime = Runtime::new().unwrap();
     runtime.set_max_stack_size(256 * 2048);
 
-    let resolver = (
-        BuiltinResolver::default()
-            // File system module
-            .with_module("fs")
-            // Operating System modules
-            .with_module("os/env")
-            // Miscellaneous modules
-            .with_module("misc/token")
-            .with_module("misc/uuid")
-            // Web modules
-            .with_module("web/http")
-            .with_module("web/website")
-            .with_module("web/json")
-            // Math module
-            .with_module("math"),
+    let mut resolver = (
+        MODULES.iter().fold(BuiltinResolver::default(), |resolver, module| resolver.with_module(*module))
+            // Native halves of the built-in modules that are written in JavaScript
+            .with_module("fs/native"),
+        SolResolver::default(),
         FileResolver::default()
             .with_path("./"),
     );
 
-    let loader = (
+    let mut loader = (
         BuiltinLoader::default()
+            // File system module
+            .with_module("fs", FS_MODULE)
             // Web modules
             .with_module("web/website", WEB_MODULE)
             .with_module("web/json", JSON_MODULE),
         ModuleLoader::default()
             // File system module
-            .with_module("fs", Fs)
+            .with_module("fs/native", Fs)
             // Operating System modules
             .with_module("os/env", Env)
+            .with_module("os/process", Process)
+            // Path module
+            .with_module("path", Path)
             // Miscellaneous modules
             .with_module("misc/uuid", Uuid)
             .with_module("misc/token", Token)
@@ -419,81 +713,174 @@ fn main() {
             .with_module("web/http", Http)
             // Math module
             .with_module("math", Math),
+        SolLoader::default(),
         ScriptLoader::default(),
     );
 
+    if let Some((name, compiled)) = script {
+        resolver.0.add_module(name);
+        loader.0.add_module(name, compiled);
+    }
+
     runtime.set_loader(resolver, loader);
 
-    let context: rquickjs::Context = Context::full(&runtime).unwrap();
-    
-    if let Some(file) = args.file {
-        let contents = read(file.clone());
-        let compiled = [
-            POLYFILL.to_string(),
-            if args.raw { contents } else { compile(&contents[..]) }
-        ].join("\n");
-
-        let fqp = std::fs::canonicalize(file.clone()).unwrap();
-        let fqd = fqp.parent().unwrap();
-    
-        if args.debug {
-            println!("=== JS OUTPUT ===");
-            println!("{}", compiled);
-        }
-
-        context.with(|ctx: rquickjs::Ctx| {
-            let glob = ctx.globals();
-    
-            // Printing to le console
-            glob.set("println", Func::from(println)).unwrap();
-            // File system
-            glob.set("__FILE__", fqp.to_str()).unwrap();
-            glob.set("__DIR__", fqd.to_str()).unwrap();
-    
-            if args.debug {
-                println!("=== EVAL ===");
-            }
-            
-            ctx.compile(file, compiled).unwrap();
-        });
-    
-        if args.debug {
-            println!("=== DEBUG ===");
-            println!("Memory used (bytes): {}", runtime.memory_usage().memory_used_size);
-        }
-    } else {
-        println!("Sol v{} | Copyright (c) 2021-2022 Joshua Colell", VERSION);
-        
-        let mut rl = Editor::<()>::new();
-
-        loop {
-            let line = rl.readline("> ");
-
-            match line {
-                Ok(line) => {
-                    rl.add_history_entry(line.as_str());
-
-                    context.with(|ctx: rquickjs::Ctx| {
-                        let glob = ctx.globals();
-                
-                        glob.set("println", Func::from(println)).unwrap();
-                
-                        ctx.eval::<(), _>(line).unwrap();
-                    });
-                },
-                Err(ReadlineError::Interrupted | ReadlineError::Eof) => {
-                    println!("Exiting Sol...");
-                    break
-                },
-                Err(e) => {
-                    println!("Error: {:?}", e);
-                    break
-                }
-            }
-        }
+    runtime
+}
+
+/// Run the JavaScript module `code` with the built-in modules, and return what it assigned to `globalThis.result`.
+#[cfg(test)]
+fn run_module(code: &str) -> String {
+    let runtime = runtime(None);
+    let context = Context::full(&runtime).unwrap();
+
+    context.with(|ctx| {
+        ctx.compile("test.mjs", code).unwrap_or_else(|e| panic!("{}: {}", code, exception::Exception::from(e)));
+        ctx.globals().get::<_, rquickjs::Coerced<String>>("result").unwrap().0
+    })
+}
+
+/// Compile the Sol source code, printing a diagnostic for each error and exiting if it fails to compile.
+fn compile_or_exit(source: &str, file: &str) -> (String, SourceMap) {
+    match compile_with_source_map(source, file) {
+        Ok(compiled) => compiled,
+        Err(error) => exit_with_diagnostics(source, file, error.diagnostics()),
+    }
+}
+
+fn exit_with_diagnostics(source: &str, file: &str, diagnostics: Vec<Diagnostic>) -> ! {
+    for diagnostic in diagnostics.iter() {
+        eprintln!("{}", diagnostic.render(source, file));
+    }
+
+    eprintln!("Could not compile `{}` due to {} previous error{}.", file, diagnostics.len(), if diagnostics.len() == 1 { "" } else { "s" });
+
+    std::process::exit(1);
+}
+
+fn exit_on_error(result: Result<(), String>) {
+    if let Err(e) = result {
+        eprintln!("{}", e);
+
+        std::process::exit(1);
+    }
+}
+
+/// The root directory of the project that the working directory is in, i.e. the closest one with a `sol.toml`.
+fn project() -> PathBuf {
+    let cwd = std::env::current_dir().unwrap();
+
+    match package::root(&cwd) {
+        Some(root) => root,
+        None => {
+            eprintln!("Could not find a `{}` in `{}` or any of its parents, run `sol init` to create one.", package::MANIFEST, cwd.display());
+
+            std::process::exit(1);
+        },
     }
 }
 
-fn read(path: String) -> String {
-    std::fs::read_to_string(path).unwrap()
-}
\ No newline at end of file
+/// The entry point of the project, for `sol run` without a file.
+fn entry() -> String {
+    let root = project();
+
+    match package::Manifest::read(&root) {
+        Ok(manifest) => root.join(manifest.package.entry).to_string_lossy().into_owned(),
+        Err(e) => {
+            eprintln!("{}", e);
+
+            std::process::exit(1);
+        },
+    }
+}
+
+fn read(path: &str) -> String {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("Could not read `{}`: {}", path, e);
+
+            std::process::exit(1);
+        },
+    }
+}
+
+fn write(path: &std::path::Path, contents: &str) {
+    if let Err(e) = std::fs::write(path, contents) {
+        eprintln!("Could not write `{}`: {}", path.display(), e);
+
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenizes() {
+        let tokens = token::Lexer::tokenize("let a = fn (b) -> b ** 2 + \"c\"".to_owned()).unwrap();
+        let tokens = tokens.iter().map(|(text, kind)| (text.as_str(), kind.as_str())).collect::<Vec<_>>();
+
+        assert_eq!(tokens, vec![
+            ("let", "Let"),
+            ("a", "Identifier"),
+            ("=", "Equals"),
+            ("fn", "Fn"),
+            ("(", "LeftParen"),
+            ("b", "Identifier"),
+            (")", "RightParen"),
+            ("->", "Arrow"),
+            ("b", "Identifier"),
+            ("**", "DoubleAsterisk"),
+            ("2", "Number"),
+            ("+", "Plus"),
+            ("c", "String"),
+        ]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn normalizes_paths() {
+        let normalize = |path: &str| path::normalize(path.to_owned());
+
+        assert_eq!(normalize("a/./b/../c"), "a/c");
+        assert_eq!(normalize("a/b/"), "a/b");
+        assert_eq!(normalize("./"), ".");
+        assert_eq!(normalize(""), ".");
+        // `..` can't go above the root, but a relative path can start with it.
+        assert_eq!(normalize("/../a/../../b"), "/b");
+        assert_eq!(normalize("a/../../b"), "../b");
+        assert_eq!(normalize("../../a"), "../../a");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn joins_paths() {
+        let join = |parts: &[&str]| path::join(rquickjs::Rest(parts.iter().map(|part| part.to_string()).collect()));
+
+        assert_eq!(join(&["a", "b/", "c.sol"]), "a/b/c.sol");
+        assert_eq!(join(&["a", "/b", "c"]), "/b/c");
+        assert_eq!(join(&["a", "../b"]), "a/../b");
+        assert_eq!(join(&[]), "");
+        assert_eq!(join(&[""]), "");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn relative_paths() {
+        let relative = |from: &str, to: &str| path::relative(from.to_owned(), to.to_owned());
+
+        assert_eq!(relative("/a/b", "/a/c"), "../c");
+        assert_eq!(relative("/a/b/c/d", "/a/e/f"), "../../../e/f");
+        assert_eq!(relative("/a", "/a/b/c"), "b/c");
+        assert_eq!(relative("/a/b/", "/a/b"), ".");
+        assert_eq!(relative("/a/./b", "/a/c/../b"), ".");
+        assert_eq!(relative("/", "/a"), "a");
+        // `..` above the root stays at the root.
+        assert_eq!(relative("/../a", "/b"), "../b");
+        // Relative paths, and empty ones, are resolved against the working directory.
+        assert_eq!(relative("", ""), ".");
+        assert_eq!(relative("a", "b"), "../b");
+        assert_eq!(relative("", "a/b"), "a/b");
+    }
+}
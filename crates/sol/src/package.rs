@@ -0,0 +1,665 @@
+use crate::{paths, MODULES};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The manifest of a project or package, which is in its root directory.
+pub const MANIFEST: &str = "sol.toml";
+/// The exact packages that `sol install` installed, next to the manifest.
+pub const LOCKFILE: &str = "sol.lock";
+/// The directory that `sol install` installs packages into, next to the manifest.
+pub const MODULES_DIR: &str = "sol_modules";
+
+type Result<T> = std::result::Result<T, String>;
+
+/// A `sol.toml`, e.g.
+///
+/// ```toml
+/// [package]
+/// name = "app"
+/// version = "0.1.0"
+/// entry = "main.sol"
+///
+/// [dependencies]
+/// strings = { path = "../strings" }
+/// colors = { tarball = "vendor/colors-1.0.0.tar.gz" }
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+    pub package: Package,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, Dependency>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    /// The file that `sol run` runs, and that is imported when another project imports the package by its name.
+    #[serde(default = "default_entry")]
+    pub entry: String,
+}
+
+fn default_entry() -> String {
+    "main.sol".to_owned()
+}
+
+/// Where a dependency comes from. Paths are relative to the manifest that lists the dependency.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum Dependency {
+    /// A directory with a `sol.toml`.
+    Path(String),
+    /// A `.tar.gz` of a directory with a `sol.toml`, either at the root of the archive or in the only directory
+    /// inside of it.
+    Tarball(String),
+}
+
+impl Manifest {
+    pub fn read(dir: &Path) -> Result<Self> {
+        let path = dir.join(MANIFEST);
+        let source = fs::read_to_string(&path).map_err(|e| format!("Could not read `{}`: {}", path.display(), e))?;
+
+        toml::from_str(&source).map_err(|e| format!("`{}` is not a valid manifest: {}", path.display(), e))
+    }
+}
+
+/// The directory of the closest `sol.toml`, looking in `dir` and then in each of its parents.
+pub fn root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors().find(|dir| dir.join(MANIFEST).is_file()).map(Path::to_path_buf)
+}
+
+/// A `sol.lock`, which records the version, source and checksum of every installed package.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Lock {
+    #[serde(default, rename = "package")]
+    packages: Vec<Locked>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Locked {
+    name: String,
+    version: String,
+    /// `path+<directory>` or `tarball+<file>`, relative to the project.
+    source: String,
+    /// The SHA-256 of the files of the package, to notice when they change without a new version.
+    checksum: String,
+}
+
+impl Lock {
+    fn read(root: &Path) -> Result<Self> {
+        let path = root.join(LOCKFILE);
+
+        match fs::read_to_string(&path) {
+            Ok(source) => toml::from_str(&source).map_err(|e| format!("`{}` is not a valid lockfile: {}", path.display(), e)),
+            Err(_) => Ok(Lock::default()),
+        }
+    }
+
+    fn write(&self, root: &Path) -> Result<()> {
+        let path = root.join(LOCKFILE);
+        let lock = toml::to_string(self).map_err(|e| format!("Could not write `{}`: {}", path.display(), e))?;
+
+        write(&path, &format!("# This file is generated by `sol install`, it is not meant to be edited by hand.\n\n{}", lock))
+    }
+}
+
+/// Create a `sol.toml` for a new project in `dir`, along with its entry point if there isn't one yet.
+pub fn init(dir: &Path, name: Option<String>) -> Result<()> {
+    if dir.join(MANIFEST).exists() {
+        return Err(format!("`{}` already exists in `{}`", MANIFEST, dir.display()));
+    }
+
+    let name = match name {
+        Some(name) => name,
+        None => dir.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| "app".to_owned()),
+    };
+
+    validate(&name)?;
+
+    write(&dir.join(MANIFEST), &format!("[package]\nname = {:?}\nversion = \"0.1.0\"\nentry = \"main.sol\"\n\n[dependencies]\n", name))?;
+
+    let entry = dir.join(default_entry());
+
+    if ! entry.exists() {
+        write(&entry, "println(\"Hello, world!\")\n")?;
+    }
+
+    println!("Created `{}` for `{}`", MANIFEST, name);
+
+    Ok(())
+}
+
+/// Add the package at `source` (a directory or a `.tar.gz`, relative to the working directory) to the
+/// dependencies of the project in `root`, and install it.
+pub fn add(root: &Path, source: &str) -> Result<()> {
+    let cwd = std::env::current_dir().map_err(|e| format!("Could not find the working directory: {}", e))?;
+    let absolute = cwd.join(source);
+    let relative = paths::relative(root, &absolute).to_string_lossy().into_owned();
+
+    let (kind, dependency) = if source.ends_with(".tar.gz") || source.ends_with(".tgz") {
+        ("tarball", Dependency::Tarball(relative.clone()))
+    } else {
+        ("path", Dependency::Path(relative.clone()))
+    };
+
+    // The package has to be unpacked to know its name.
+    let package = Source::new(&dependency, root)?.fetch()?;
+    let name = package.manifest.package.name;
+
+    validate(&name)?;
+
+    // The manifest is edited instead of written from scratch, to keep its comments and formatting.
+    let path = root.join(MANIFEST);
+    let mut manifest = fs::read_to_string(&path)
+        .map_err(|e| format!("Could not read `{}`: {}", path.display(), e))?
+        .parse::<toml_edit::Document>()
+        .map_err(|e| format!("`{}` is not a valid manifest: {}", path.display(), e))?;
+
+    let dependencies = manifest.entry("dependencies").or_insert_with(toml_edit::table);
+    let dependencies = dependencies.as_table_like_mut()
+        .ok_or_else(|| format!("`dependencies` in `{}` is not a table", path.display()))?;
+
+    let mut table = toml_edit::InlineTable::new();
+    table.insert(kind, relative.into());
+
+    dependencies.insert(&name, toml_edit::value(table));
+
+    let edited = manifest.to_string();
+
+    // The manifest is only written once the dependency is installed, so that a failed install leaves it as it was.
+    install_manifest(root, toml::from_str(&edited).map_err(|e| format!("`{}` is not a valid manifest: {}", path.display(), e))?)?;
+
+    write(&path, &edited)?;
+
+    println!("Added `{}` v{} to `{}`", name, package.manifest.package.version, MANIFEST);
+
+    Ok(())
+}
+
+/// Install the dependencies of the project in `root`, and all of their dependencies, into `sol_modules`.
+///
+/// Every package is installed once, directly in `sol_modules`, so two packages that depend on the same package
+/// have to get it from the same place. Packages that haven't changed since the last install are left alone.
+pub fn install(root: &Path) -> Result<()> {
+    install_manifest(root, Manifest::read(root)?)
+}
+
+/// Install the dependencies of `manifest`, the manifest of the project in `root`.
+fn install_manifest(root: &Path, manifest: Manifest) -> Result<()> {
+    let previous = Lock::read(root)?;
+    let modules = root.join(MODULES_DIR);
+
+    // The dependencies still to resolve, with the directory that their paths are relative to and who needs them.
+    let mut pending = manifest.dependencies.into_iter()
+        .map(|(name, dependency)| (name, dependency, root.to_path_buf(), manifest.package.name.clone()))
+        .collect::<Vec<_>>();
+
+    let mut resolved: BTreeMap<String, (Source, Fetched)> = BTreeMap::new();
+
+    while ! pending.is_empty() {
+        let (name, dependency, dir, dependent) = pending.remove(0);
+
+        validate(&name)?;
+
+        let source = Source::new(&dependency, &dir)?;
+
+        if let Some((other, _)) = resolved.get(&name) {
+            if *other == source {
+                continue;
+            }
+
+            return Err(format!(
+                "`{}` needs `{}` from `{}`, but it is already installed from `{}`", dependent, name,
+                source.path().display(), other.path().display(),
+            ));
+        }
+
+        let package = source.fetch()?;
+
+        if package.manifest.package.name != name {
+            return Err(format!("The package at `{}` is called `{}`, not `{}`", source.path().display(), package.manifest.package.name, name));
+        }
+
+        // Paths in the manifest of a tarball are relative to where the tarball is.
+        let base = match &source {
+            Source::Path(path) => path.clone(),
+            Source::Tarball(path) => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        };
+
+        pending.extend(package.manifest.dependencies.iter()
+            .map(|(dependency, source)| (dependency.clone(), source.clone(), base.clone(), name.clone())));
+
+        resolved.insert(name, (source, package));
+    }
+
+    let mut lock = Lock::default();
+
+    for (name, (source, package)) in resolved.iter() {
+        let locked = Locked {
+            name: name.clone(),
+            version: package.manifest.package.version.clone(),
+            source: source.describe(root),
+            checksum: checksum(&package.dir)?,
+        };
+
+        let target = modules.join(name);
+
+        if ! previous.packages.contains(&locked) || ! target.is_dir() {
+            remove(&target)?;
+            copy(&package.dir, &target)?;
+
+            let action = if previous.packages.iter().any(|old| old.name == *name) { "Updated" } else { "Installed" };
+
+            println!("{} `{}` v{} from `{}`", action, name, locked.version, source.path().display());
+        }
+
+        lock.packages.push(locked);
+    }
+
+    for old in previous.packages.iter().filter(|old| ! resolved.contains_key(&old.name)) {
+        remove(&modules.join(&old.name))?;
+
+        println!("Removed `{}` v{}", old.name, old.version);
+    }
+
+    lock.write(root)?;
+
+    println!("{} package{} installed", resolved.len(), if resolved.len() == 1 { "" } else { "s" });
+
+    Ok(())
+}
+
+/// Package names are used to import packages, so they can't look like paths or be taken by built-in modules.
+fn validate(name: &str) -> Result<()> {
+    let valid = ! name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+
+    if ! valid {
+        return Err(format!("`{}` is not a valid package name, it can only contain letters, digits, `_` and `-`", name));
+    }
+
+    if MODULES.contains(&name) {
+        return Err(format!("`{}` is not a valid package name, it is the name of a built-in module", name));
+    }
+
+    Ok(())
+}
+
+/// A dependency with its path made absolute.
+#[derive(Debug, PartialEq)]
+enum Source {
+    Path(PathBuf),
+    Tarball(PathBuf),
+}
+
+/// The files of a package that is about to be installed.
+struct Fetched {
+    manifest: Manifest,
+    /// The directory with the `sol.toml` of the package.
+    dir: PathBuf,
+    /// Where a tarball was unpacked, which is removed once the package is installed.
+    _unpacked: Option<TempDir>,
+}
+
+impl Source {
+    fn new(dependency: &Dependency, dir: &Path) -> Result<Self> {
+        let (path, tarball) = match dependency {
+            Dependency::Path(path) => (path, false),
+            Dependency::Tarball(path) => (path, true),
+        };
+
+        let path = dir.join(path).canonicalize().map_err(|e| format!("Could not find `{}`: {}", dir.join(path).display(), e))?;
+
+        Ok(if tarball { Source::Tarball(path) } else { Source::Path(path) })
+    }
+
+    fn path(&self) -> &Path {
+        match self {
+            Source::Path(path) | Source::Tarball(path) => path,
+        }
+    }
+
+    /// How the lockfile refers to the source, relative to the project in `root`.
+    fn describe(&self, root: &Path) -> String {
+        let relative = paths::relative(root, self.path()).to_string_lossy().into_owned();
+
+        match self {
+            Source::Path(_) => format!("path+{}", relative),
+            Source::Tarball(_) => format!("tarball+{}", relative),
+        }
+    }
+
+    fn fetch(&self) -> Result<Fetched> {
+        let (dir, unpacked) = match self {
+            Source::Path(path) => (path.clone(), None),
+            Source::Tarball(path) => {
+                let unpacked = unpack(path)?;
+
+                (package_dir(unpacked.path()).ok_or_else(|| format!("`{}` doesn't contain a `{}`", path.display(), MANIFEST))?, Some(unpacked))
+            },
+        };
+
+        Ok(Fetched { manifest: Manifest::read(&dir)?, dir, _unpacked: unpacked })
+    }
+}
+
+/// A directory in the temporary directory of the system, which is removed when it's dropped.
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    /// A new, empty directory with a random name.
+    pub(crate) fn new() -> Result<Self> {
+        let dir = TempDir(std::env::temp_dir().join(format!("sol-{:016x}", rand::random::<u64>())));
+
+        fs::create_dir_all(&dir.0).map_err(|e| format!("Could not create `{}`: {}", dir.0.display(), e))?;
+
+        Ok(dir)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn unpack(tarball: &Path) -> Result<TempDir> {
+    let file = fs::File::open(tarball).map_err(|e| format!("Could not read `{}`: {}", tarball.display(), e))?;
+    let dir = TempDir::new()?;
+
+    tar::Archive::new(flate2::read::GzDecoder::new(file))
+        .unpack(dir.path())
+        .map_err(|e| format!("Could not unpack `{}`: {}", tarball.display(), e))?;
+
+    Ok(dir)
+}
+
+/// The directory with the manifest in an unpacked tarball, which is the root or the only directory in it.
+fn package_dir(unpacked: &Path) -> Option<PathBuf> {
+    if unpacked.join(MANIFEST).is_file() {
+        return Some(unpacked.to_path_buf());
+    }
+
+    let entries = fs::read_dir(unpacked).ok()?.collect::<std::io::Result<Vec<_>>>().ok()?;
+
+    match entries.as_slice() {
+        [entry] if entry.path().join(MANIFEST).is_file() => Some(entry.path()),
+        _ => None,
+    }
+}
+
+/// The files of the package in `dir`, relative to it and sorted. Installed packages and version control are
+/// left out, since they aren't part of the package, and so are links to directories.
+fn files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+
+    while let Some(relative) = pending.pop() {
+        let entries = fs::read_dir(dir.join(&relative))
+            .and_then(|entries| entries.collect::<std::io::Result<Vec<_>>>())
+            .map_err(|e| format!("Could not read `{}`: {}", dir.join(&relative).display(), e))?;
+
+        for entry in entries {
+            let name = entry.file_name();
+
+            if relative.as_os_str().is_empty() && (name == MODULES_DIR || name == ".git") {
+                continue;
+            }
+
+            // Symbolic links to directories aren't followed, since they could point back into the package.
+            let kind = entry.file_type().map_err(|e| format!("Could not read `{}`: {}", entry.path().display(), e))?;
+
+            if kind.is_dir() {
+                pending.push(relative.join(name));
+            } else if kind.is_file() || entry.path().is_file() {
+                files.push(relative.join(name));
+            }
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+fn checksum(dir: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+
+    for file in files(dir)? {
+        let contents = fs::read(dir.join(&file)).map_err(|e| format!("Could not read `{}`: {}", dir.join(&file).display(), e))?;
+
+        // The path and length keep moving bytes from one file to the next from giving the same checksum.
+        hasher.update(file.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn copy(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to).map_err(|e| format!("Could not create `{}`: {}", to.display(), e))?;
+
+    for file in files(from)? {
+        let target = to.join(&file);
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Could not create `{}`: {}", parent.display(), e))?;
+        }
+
+        fs::copy(from.join(&file), &target).map_err(|e| format!("Could not copy `{}`: {}", from.join(&file).display(), e))?;
+    }
+
+    Ok(())
+}
+
+fn remove(path: &Path) -> Result<()> {
+    if path.exists() {
+        fs::remove_dir_all(path).map_err(|e| format!("Could not remove `{}`: {}", path.display(), e))?;
+    }
+
+    Ok(())
+}
+
+fn write(path: &Path, contents: &str) -> Result<()> {
+    fs::write(path, contents).map_err(|e| format!("Could not write `{}`: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A temporary directory with `files`, and the canonical path to it.
+    fn project(files: &[(&str, &str)]) -> (TempDir, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().canonicalize().unwrap();
+
+        for (name, contents) in files {
+            fs::create_dir_all(path.join(name).parent().unwrap()).unwrap();
+            fs::write(path.join(name), contents).unwrap();
+        }
+
+        (dir, path)
+    }
+
+    fn package(name: &str, dependencies: &str) -> String {
+        format!("[package]\nname = \"{}\"\nversion = \"1.0.0\"\n\n[dependencies]\n{}", name, dependencies)
+    }
+
+    fn locked(root: &Path) -> Vec<(String, String)> {
+        Lock::read(root).unwrap().packages.into_iter().map(|locked| (locked.name, locked.source)).collect()
+    }
+
+    #[test]
+    fn inits() {
+        let (_dir, path) = project(&[("app/main.sol", "println(1)\n")]);
+        let root = path.join("app");
+
+        init(&root, None).unwrap();
+
+        let manifest = Manifest::read(&root).unwrap();
+        assert_eq!((manifest.package.name.as_str(), manifest.package.version.as_str()), ("app", "0.1.0"));
+        assert!(manifest.dependencies.is_empty());
+        // An existing entry point is kept.
+        assert_eq!(fs::read_to_string(root.join("main.sol")).unwrap(), "println(1)\n");
+
+        assert_eq!(init(&root, None), Err(format!("`sol.toml` already exists in `{}`", root.display())));
+
+        let other = path.join("other");
+        fs::create_dir(&other).unwrap();
+
+        assert!(init(&other, Some("fs".to_owned())).unwrap_err().contains("it is the name of a built-in module"));
+        assert!(init(&other, Some("../app".to_owned())).unwrap_err().contains("is not a valid package name"));
+
+        init(&other, Some("tool".to_owned())).unwrap();
+        assert_eq!(Manifest::read(&other).unwrap().package.name, "tool");
+        assert_eq!(fs::read_to_string(other.join("main.sol")).unwrap(), "println(\"Hello, world!\")\n");
+    }
+
+    #[test]
+    fn adds_paths() {
+        let (_dir, path) = project(&[
+            ("app/sol.toml", "# The app.\n[package]\nname = \"app\"\nversion = \"0.1.0\"\n"),
+            ("strings/sol.toml", &package("strings", "")),
+            ("strings/main.sol", "export fn pad(s) {\n    return s\n}\n"),
+            ("strings/sol_modules/old/main.sol", ""),
+        ]);
+        let root = path.join("app");
+
+        add(&root, path.join("strings").to_str().unwrap()).unwrap();
+
+        let manifest = fs::read_to_string(root.join(MANIFEST)).unwrap();
+        assert!(manifest.starts_with("# The app.\n"));
+        assert!(manifest.contains("strings = { path = \"../strings\" }"));
+
+        assert!(root.join("sol_modules/strings/main.sol").is_file());
+        // Packages that the package installed itself aren't part of it.
+        assert!(! root.join("sol_modules/strings/sol_modules").exists());
+        assert_eq!(locked(&root), vec![("strings".to_owned(), "path+../strings".to_owned())]);
+    }
+
+    #[test]
+    fn adds_tarballs() {
+        let (_dir, path) = project(&[
+            ("app/sol.toml", &package("app", "")),
+            ("colors/sol.toml", &package("colors", "")),
+            ("colors/main.sol", "export let red = \"#f00\"\n"),
+        ]);
+        let root = path.join("app");
+        let tarball = path.join("colors-1.0.0.tar.gz");
+
+        let mut archive = tar::Builder::new(flate2::write::GzEncoder::new(fs::File::create(&tarball).unwrap(), flate2::Compression::default()));
+        archive.append_dir_all("colors", path.join("colors")).unwrap();
+        archive.into_inner().unwrap().finish().unwrap();
+
+        add(&root, tarball.to_str().unwrap()).unwrap();
+
+        assert!(fs::read_to_string(root.join(MANIFEST)).unwrap().contains("colors = { tarball = \"../colors-1.0.0.tar.gz\" }"));
+        assert_eq!(fs::read_to_string(root.join("sol_modules/colors/main.sol")).unwrap(), "export let red = \"#f00\"\n");
+        assert_eq!(locked(&root), vec![("colors".to_owned(), "tarball+../colors-1.0.0.tar.gz".to_owned())]);
+    }
+
+    #[test]
+    fn keeps_the_manifest_when_adding_fails() {
+        let (_dir, path) = project(&[
+            ("app/sol.toml", &package("app", "")),
+            ("strings/sol.toml", &package("strings", "missing = { path = \"../missing\" }\n")),
+        ]);
+        let root = path.join("app");
+
+        assert!(add(&root, path.join("strings").to_str().unwrap()).unwrap_err().starts_with("Could not find"));
+
+        assert_eq!(fs::read_to_string(root.join(MANIFEST)).unwrap(), package("app", ""));
+        assert!(! root.join(LOCKFILE).exists());
+    }
+
+    #[test]
+    fn reinstalls_changed_packages() {
+        let (_dir, path) = project(&[
+            ("app/sol.toml", &package("app", "strings = { path = \"../strings\" }\n")),
+            ("strings/sol.toml", &package("strings", "")),
+            ("strings/main.sol", "export let a = 1\n"),
+        ]);
+        let root = path.join("app");
+
+        install(&root).unwrap();
+
+        let lock = fs::read_to_string(root.join(LOCKFILE)).unwrap();
+        // A file that only the installed copy has, to tell whether it was installed again.
+        fs::write(root.join("sol_modules/strings/marker"), "").unwrap();
+
+        install(&root).unwrap();
+
+        assert_eq!(fs::read_to_string(root.join(LOCKFILE)).unwrap(), lock);
+        assert!(root.join("sol_modules/strings/marker").exists());
+
+        fs::write(path.join("strings/main.sol"), "export let a = 2\n").unwrap();
+        install(&root).unwrap();
+
+        assert_ne!(fs::read_to_string(root.join(LOCKFILE)).unwrap(), lock);
+        assert!(! root.join("sol_modules/strings/marker").exists());
+        assert_eq!(fs::read_to_string(root.join("sol_modules/strings/main.sol")).unwrap(), "export let a = 2\n");
+
+        // Dependencies that are no longer in the manifest are removed.
+        fs::write(root.join(MANIFEST), package("app", "")).unwrap();
+        install(&root).unwrap();
+
+        assert!(! root.join("sol_modules/strings").exists());
+        assert!(locked(&root).is_empty());
+    }
+
+    #[test]
+    fn rejects_conflicting_sources() {
+        let (_dir, path) = project(&[
+            ("app/sol.toml", &package("app", "strings = { path = \"../strings\" }\nformat = { path = \"../format\" }\n")),
+            ("strings/sol.toml", &package("strings", "")),
+            ("format/sol.toml", &package("format", "strings = { path = \"vendor/strings\" }\n")),
+            ("format/vendor/strings/sol.toml", &package("strings", "")),
+        ]);
+
+        assert_eq!(install(&path.join("app")), Err(format!(
+            "`format` needs `strings` from `{}`, but it is already installed from `{}`",
+            path.join("format/vendor/strings").display(),
+            path.join("strings").display(),
+        )));
+    }
+
+    #[test]
+    fn rejects_renamed_packages() {
+        let (_dir, path) = project(&[
+            ("app/sol.toml", &package("app", "text = { path = \"../strings\" }\n")),
+            ("strings/sol.toml", &package("strings", "")),
+        ]);
+
+        assert_eq!(install(&path.join("app")), Err(format!(
+            "The package at `{}` is called `strings`, not `text`",
+            path.join("strings").display(),
+        )));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skips_linked_directories() {
+        let (_dir, path) = project(&[
+            ("strings/sol.toml", &package("strings", "")),
+            ("strings/lib/pad.sol", ""),
+        ]);
+        let package = path.join("strings");
+
+        std::os::unix::fs::symlink(&package, package.join("lib/parent")).unwrap();
+        std::os::unix::fs::symlink(package.join("lib/pad.sol"), package.join("pad.sol")).unwrap();
+
+        assert_eq!(files(&package).unwrap(), vec![
+            PathBuf::from("lib/pad.sol"),
+            PathBuf::from("pad.sol"),
+            PathBuf::from("sol.toml"),
+        ]);
+    }
+}
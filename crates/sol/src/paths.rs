@@ -0,0 +1,48 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Resolve `.` and `..` without looking at the file system, so that the path doesn't have to exist.
+pub fn lexical(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                },
+                // `..` can't go above the root.
+                Some(Component::RootDir | Component::Prefix(_)) => {},
+                _ => normalized.push(".."),
+            },
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// The absolute, normalized version of the path, relative to the working directory.
+pub fn absolute(path: &Path) -> PathBuf {
+    let cwd = std::env::current_dir().unwrap_or_default();
+
+    lexical(&cwd.join(path))
+}
+
+/// The path that leads from `from` to `to`, or `.` if they are the same. Relative paths are resolved against the
+/// working directory first.
+pub fn relative(from: &Path, to: &Path) -> PathBuf {
+    let (from, to) = (absolute(from), absolute(to));
+    let common = from.components().zip(to.components()).take_while(|(a, b)| a == b).count();
+
+    // On Windows, paths on different drives have nothing in common.
+    if common == 0 {
+        return to;
+    }
+
+    let relative = from.components().skip(common).map(|_| Component::ParentDir)
+        .chain(to.components().skip(common))
+        .collect::<PathBuf>();
+
+    if relative.as_os_str().is_empty() { PathBuf::from(".") } else { relative }
+}
@@ -0,0 +1,86 @@
+use crate::exception::exception;
+use rquickjs::{Created, Ctx, Func, IntoJs, Loaded, Module, ModuleDef, Native, Null, Object, Opt, Result, Value};
+use std::io::{BufRead, Read, Write};
+use std::sync::OnceLock;
+
+static ARGS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Set the arguments that were passed to the script, which `os/process` exports as `args`.
+pub fn set_args(args: Vec<String>) {
+    let _ = ARGS.set(args);
+}
+
+/// The `os/process` module, for scripts that are used as command-line tools.
+///
+/// Its exports depend on how Sol was started, which is why it isn't defined with `#[bind]` like the other
+/// built-in modules.
+pub struct Process;
+
+const EXPORTS: &[&str] = &["args", "exit", "stdin", "stderr", "pid", "cwd"];
+
+impl ModuleDef for Process {
+    fn load<'js>(_: Ctx<'js>, module: &Module<'js, Created>) -> Result<()> {
+        for export in EXPORTS {
+            module.add(*export)?;
+        }
+
+        Ok(())
+    }
+
+    fn eval<'js>(ctx: Ctx<'js>, module: &Module<'js, Loaded<Native>>) -> Result<()> {
+        let stdin = Object::new(ctx)?;
+        stdin.set("readLine", Func::from(read_line))?;
+        stdin.set("readAll", Func::from(read_all))?;
+
+        let stderr = Object::new(ctx)?;
+        stderr.set("write", Func::from(write_stderr))?;
+
+        module.set("args", ARGS.get().cloned().unwrap_or_default())?;
+        module.set("exit", Func::from(exit))?;
+        module.set("stdin", stdin)?;
+        module.set("stderr", stderr)?;
+        module.set("pid", std::process::id())?;
+        module.set("cwd", std::env::current_dir().map(|dir| dir.to_string_lossy().into_owned()).unwrap_or_default())?;
+
+        Ok(())
+    }
+}
+
+/// Exit with `code`, or 0 when it isn't given.
+fn exit(code: Opt<i32>) {
+    // Anything that was printed without a newline is still buffered.
+    let _ = std::io::stdout().flush();
+
+    std::process::exit(code.0.unwrap_or(0));
+}
+
+/// Read a line from stdin without the line ending, or `null` once there is nothing left to read.
+fn read_line(ctx: Ctx) -> Result<Value> {
+    let mut line = String::new();
+
+    match std::io::stdin().lock().read_line(&mut line) {
+        // `None` would become `undefined`, which doesn't equal `null` in Sol.
+        Ok(0) => Null.into_js(ctx),
+        Ok(_) => line.trim_end_matches('\n').trim_end_matches('\r').into_js(ctx),
+        Err(e) => Err(exception(format!("Could not read from stdin: {}", e))),
+    }
+}
+
+/// Read everything that is left on stdin.
+fn read_all() -> Result<String> {
+    let mut contents = String::new();
+
+    std::io::stdin().lock().read_to_string(&mut contents)
+        .map_err(|e| exception(format!("Could not read from stdin: {}", e)))?;
+
+    Ok(contents)
+}
+
+/// Write `text` to stderr as it is, without adding a newline.
+fn write_stderr(text: String) -> Result<()> {
+    let mut stderr = std::io::stderr();
+
+    stderr.write_all(text.as_bytes())
+        .and_then(|_| stderr.flush())
+        .map_err(|e| exception(format!("Could not write to stderr: {}", e)))
+}
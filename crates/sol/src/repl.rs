@@ -0,0 +1,454 @@
+use crate::{display, Exception, MODULES, POLYFILL, VERSION};
+use rquickjs::{Context, Ctx, Filter, Runtime, Type, Undefined, Value};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Editor, Helper};
+use sol_compiler::{compile, compile_interactive, lex, parse, Lexer, LexErrorType, TokenKind, KEYWORDS};
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+const HELP: &str = "\
+:tokens <code>  Print the tokens of the code
+:ast <code>     Print the syntax tree of the code
+:js <code>      Print the JavaScript that the code compiles to
+:load <file>    Run a Sol file in the current session
+:reset          Forget every binding and start a fresh session
+:help           Print this message
+
+Input continues on the next line while it is incomplete. Enter an empty line to submit it anyway.";
+
+/// An interactive session, which compiles and evaluates each input that is typed in.
+struct Repl<'r> {
+    runtime: &'r Runtime,
+    context: Context,
+    inputs: usize,
+}
+
+/// Start an interactive session, reading input until the user exits.
+pub fn start(runtime: &Runtime, context: Context) {
+    println!("Sol v{} | Copyright (c) 2021-2022 Joshua Colell", VERSION);
+    println!("Type :help for a list of commands.");
+
+    let mut repl = Repl::new(runtime, context);
+    let mut rl = Editor::<SolHelper>::new();
+    rl.set_helper(Some(SolHelper { context: repl.context.clone() }));
+
+    let history = history_path();
+
+    if let Some(history) = &history {
+        // There's no history the first time the REPL is started.
+        let _ = rl.load_history(history);
+    }
+
+    loop {
+        let line = rl.readline("> ");
+
+        match line {
+            Ok(line) => {
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                rl.add_history_entry(line.as_str());
+
+                if let Some(history) = &history {
+                    if let Err(e) = rl.save_history(history) {
+                        eprintln!("Could not save history to `{}`: {}", history.display(), e);
+                    }
+                }
+
+                repl.input(line.trim_end());
+
+                // `:reset` replaces the context, so completions need to come from the new one.
+                if let Some(helper) = rl.helper_mut() {
+                    helper.context = repl.context.clone();
+                }
+            },
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => {
+                println!("Exiting Sol...");
+                break
+            },
+            Err(e) => {
+                println!("Error: {:?}", e);
+                break
+            }
+        }
+    }
+}
+
+/// The file that history is kept in between sessions, in the user's home directory.
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".sol_history"))
+}
+
+impl<'r> Repl<'r> {
+    fn new(runtime: &'r Runtime, context: Context) -> Self {
+        setup(&context);
+
+        Self { runtime, context, inputs: 0 }
+    }
+
+    /// Handle a single input, which is either a meta-command or Sol code.
+    fn input(&mut self, input: &str) {
+        let (command, argument) = match input.strip_prefix(':') {
+            Some(command) => command.split_once(char::is_whitespace).unwrap_or((command, "")),
+            None => return self.eval(input, "<repl>"),
+        };
+
+        let argument = argument.trim();
+
+        match command {
+            "tokens" => match lex(argument) {
+                Ok(tokens) => for token in tokens {
+                    println!("{}:{}\t{}", token.span.line, token.span.column, token.kind);
+                },
+                Err(e) => eprintln!("{}", sol_compiler::Diagnostic::from(&e).render(argument, "<repl>")),
+            },
+            "ast" => match parse(argument) {
+                Ok(program) => println!("{:#?}", program),
+                Err(errors) => report(argument, "<repl>", errors.into()),
+            },
+            "js" => match compile(argument) {
+                Ok(compiled) => println!("{}", compiled.trim_end()),
+                Err(error) => report(argument, "<repl>", error),
+            },
+            "load" if argument.is_empty() => eprintln!("Usage: :load <file>"),
+            "load" => match std::fs::read_to_string(argument) {
+                Ok(source) => self.eval(&source, argument),
+                Err(e) => eprintln!("Could not read `{}`: {}", argument, e),
+            },
+            "reset" => {
+                self.context = Context::full(self.runtime).unwrap();
+                setup(&self.context);
+
+                println!("Session reset.");
+            },
+            "help" => println!("{}", HELP),
+            _ => eprintln!("Unknown command `:{}`. Type :help for a list of commands.", command),
+        }
+    }
+
+    /// Compile and evaluate Sol code from `file`, printing its value or whatever went wrong.
+    fn eval(&mut self, input: &str, file: &str) {
+        let compiled = match compile_interactive(input) {
+            Ok(compiled) => compiled,
+            Err(error) => return report(input, file, error),
+        };
+
+        // Every module needs a name of its own, even when the same file is loaded twice.
+        self.inputs += 1;
+        let name = format!("<repl:{}>", self.inputs);
+
+        self.context.with(|ctx: Ctx| {
+            let globals = ctx.globals();
+
+            globals.set("__sol_value", Undefined).unwrap();
+            globals.set("__sol_uncaught", Undefined).unwrap();
+
+            // Each input is a module, so that it can import other modules.
+            if let Err(e) = ctx.compile(name, compiled) {
+                eprintln!("Uncaught {}", Exception::from(e));
+                return;
+            }
+
+            let uncaught = globals.get::<_, Value>("__sol_uncaught").unwrap();
+
+            if ! uncaught.type_of().is_void() {
+                eprintln!("Uncaught {}", Exception::from_value(uncaught));
+                return;
+            }
+
+            let value = globals.get::<_, Value>("__sol_value").unwrap();
+
+            if value.type_of() != Type::Undefined {
+                println!("{}", display::inspect(ctx, &value));
+            }
+        });
+    }
+}
+
+/// Define the globals that every session starts with.
+fn setup(context: &Context) {
+    context.with(|ctx: Ctx| {
+        display::register(ctx).unwrap();
+
+        // The polyfill is evaluated as a script once, so that its declarations are visible to every input.
+        ctx.eval::<(), _>(POLYFILL).unwrap();
+    });
+}
+
+fn report(input: &str, file: &str, error: sol_compiler::CompileError) {
+    for diagnostic in error.diagnostics() {
+        eprintln!("{}", diagnostic.render(input, file));
+    }
+}
+
+/// Completes, highlights and validates the input as it is typed.
+struct SolHelper {
+    context: Context,
+}
+
+impl SolHelper {
+    /// The names of the properties of the object at `path`, including inherited ones like the methods of
+    /// an array.
+    fn properties(&self, path: &[&str]) -> Vec<String> {
+        self.context.with(|ctx: Ctx| {
+            let mut object = ctx.globals();
+
+            for name in path {
+                object = match object.get::<_, Value>(*name).map(|v| v.into_object()) {
+                    Ok(Some(o)) => o,
+                    _ => return Vec::new(),
+                };
+            }
+
+            let mut names = Vec::new();
+            let mut current = Some(object);
+
+            while let Some(object) = current {
+                names.extend(object.own_keys::<String>(Filter::new().string()).filter_map(Result::ok));
+                current = object.get_prototype().ok();
+            }
+
+            names
+        })
+    }
+
+    /// The names of the global bindings, including the ones that JavaScript defines like `Math`.
+    fn globals(&self) -> Vec<String> {
+        self.context.with(|ctx: Ctx| {
+            ctx.globals().own_keys::<String>(Filter::new().string()).filter_map(Result::ok).collect()
+        })
+    }
+}
+
+impl Helper for SolHelper {}
+
+impl Completer for SolHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _: &rustyline::Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        let before = &line[..pos];
+
+        // Inside of a string, the only thing worth completing is the name of a module to import.
+        if before.matches('"').count() % 2 == 1 {
+            let start = before.rfind('"').unwrap() + 1;
+
+            if ! before[..start].contains("import") {
+                return Ok((pos, Vec::new()));
+            }
+
+            let modules = MODULES.iter()
+                .filter(|m| m.starts_with(&before[start..]))
+                .map(|m| m.to_string())
+                .collect();
+
+            return Ok((start, modules));
+        }
+
+        let start = before.char_indices().rev()
+            .find(|(_, c)| ! is_identifier_char(*c))
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0);
+        let word = &before[start..];
+
+        let mut candidates = match before[..start].strip_suffix('.') {
+            Some(object) => {
+                let path = &object[object.len() - object.chars().rev().take_while(|c| is_identifier_char(*c) || *c == '.').map(char::len_utf8).sum::<usize>()..];
+                let path = path.split('.').collect::<Vec<&str>>();
+
+                // Only plain paths like `a.b.` are looked up, since anything else would have to be evaluated.
+                if path.iter().any(|name| name.is_empty()) {
+                    return Ok((start, Vec::new()));
+                }
+
+                self.properties(&path)
+            },
+            None => KEYWORDS.iter().map(|k| k.to_string()).chain(self.globals()).collect(),
+        };
+
+        // Names starting with `__` are internal, like `__sol_value` and `__proto__`.
+        candidates.retain(|c| c.starts_with(word) && ! c.starts_with("__"));
+        candidates.sort();
+        candidates.dedup();
+
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for SolHelper {
+    type Hint = String;
+}
+
+const RESET: &str = "\x1b[0m";
+const KEYWORD: &str = "\x1b[35m";
+const LITERAL: &str = "\x1b[33m";
+const STRING: &str = "\x1b[32m";
+const COMMENT: &str = "\x1b[90m";
+
+impl Highlighter for SolHelper {
+    fn highlight<'l>(&self, line: &'l str, _: usize) -> Cow<'l, str> {
+        let mut output = String::with_capacity(line.len());
+        let mut end = 0;
+
+        // The lexer only skips whitespace and comments, so anything in between tokens that isn't whitespace is
+        // a comment.
+        let gap = |output: &mut String, gap: &str| if gap.trim().is_empty() {
+            output.push_str(gap);
+        } else {
+            paint(output, gap, COMMENT);
+        };
+
+        for token in Lexer::new(line) {
+            let token = match token {
+                Ok(token) => token,
+                Err(e) => {
+                    gap(&mut output, &line[end..e.span.start]);
+
+                    match e.err {
+                        LexErrorType::UnterminatedString => paint(&mut output, &line[e.span.start..], STRING),
+                        LexErrorType::UnterminatedComment => paint(&mut output, &line[e.span.start..], COMMENT),
+                        _ => output.push_str(&line[e.span.start..]),
+                    }
+
+                    return Cow::Owned(output);
+                },
+            };
+
+            gap(&mut output, &line[end..token.span.start]);
+
+            let text = &line[token.span.start..token.span.end];
+
+            match token.kind {
+                TokenKind::Fn | TokenKind::Let | TokenKind::If | TokenKind::Else | TokenKind::While |
+                TokenKind::Return | TokenKind::Break | TokenKind::Continue | TokenKind::Import |
+                TokenKind::From | TokenKind::As | TokenKind::Export => paint(&mut output, text, KEYWORD),
+                TokenKind::True | TokenKind::False | TokenKind::Number(_) => paint(&mut output, text, LITERAL),
+                TokenKind::String(_) => paint(&mut output, text, STRING),
+                _ => output.push_str(text),
+            }
+
+            end = token.span.end;
+        }
+
+        gap(&mut output, &line[end..]);
+
+        Cow::Owned(output)
+    }
+
+    fn highlight_char(&self, _: &str, _: usize) -> bool {
+        // Every character can change how the rest of the line is lexed, e.g. by opening a string.
+        true
+    }
+}
+
+fn paint(output: &mut String, text: &str, colour: &str) {
+    output.push_str(colour);
+    output.push_str(text);
+    output.push_str(RESET);
+}
+
+/// Whether `c` can be part of an identifier, the way the lexer reads them.
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+impl Validator for SolHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        let input = ctx.input();
+
+        // An empty line submits the input as it is, so that a typo can't keep the prompt open forever.
+        if input.starts_with(':') || input.ends_with('\n') || ! is_incomplete(input) {
+            Ok(ValidationResult::Valid(None))
+        } else {
+            Ok(ValidationResult::Incomplete)
+        }
+    }
+}
+
+/// Whether `input` is the start of some code that continues on the next line, rather than code that is
+/// finished (or wrong).
+fn is_incomplete(input: &str) -> bool {
+    let tokens = match lex(input) {
+        Ok(tokens) => tokens,
+        Err(e) => return matches!(e.err, LexErrorType::UnterminatedString | LexErrorType::UnterminatedComment),
+    };
+
+    let depth = tokens.iter().fold(0, |depth, token| match token.kind {
+        TokenKind::LeftParen | TokenKind::LeftBrace | TokenKind::LeftBracket => depth + 1,
+        TokenKind::RightParen | TokenKind::RightBrace | TokenKind::RightBracket => depth - 1,
+        _ => depth,
+    });
+
+    if depth > 0 {
+        return true;
+    }
+
+    // An expression can't end with an operator, so the rest of it must be on the next line.
+    matches!(tokens.iter().rev().find(|t| t.kind != TokenKind::Eof).map(|t| &t.kind), Some(
+        TokenKind::Plus | TokenKind::Minus | TokenKind::Asterisk | TokenKind::Slash | TokenKind::Percent |
+        TokenKind::DoubleAsterisk | TokenKind::And | TokenKind::Or | TokenKind::Not |
+        TokenKind::Equals | TokenKind::PlusEquals | TokenKind::MinusEquals | TokenKind::AsteriskEquals |
+        TokenKind::SlashEquals | TokenKind::EqualsEquals | TokenKind::NotEquals | TokenKind::GreaterThan |
+        TokenKind::GreaterThanEquals | TokenKind::LessThan | TokenKind::LessThanEquals |
+        TokenKind::Colon | TokenKind::DoubleColon | TokenKind::Comma | TokenKind::Dot | TokenKind::Arrow
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustyline::history::History;
+
+    fn complete(line: &str) -> (usize, Vec<String>) {
+        let runtime = Runtime::new().unwrap();
+        let helper = SolHelper { context: Context::full(&runtime).unwrap() };
+
+        helper.context.with(|ctx: Ctx| {
+            ctx.eval::<(), _>("globalThis.$value = 1; globalThis.$values = { $first: 1, second: 2 }; globalThis.value = 3").unwrap();
+        });
+
+        helper.complete(line, line.len(), &rustyline::Context::new(&History::new())).unwrap()
+    }
+
+    #[test]
+    fn incomplete_input() {
+        assert!(is_incomplete("fn f() {"));
+        assert!(is_incomplete("let a = [1, [2]"));
+        assert!(is_incomplete("f(1,"));
+        assert!(is_incomplete("let a = 1 +"));
+        assert!(is_incomplete("let s = \"unterminated"));
+        assert!(is_incomplete("/* a comment"));
+
+        assert!(! is_incomplete("fn f() {}"));
+        assert!(! is_incomplete("let a = [1, [2]]"));
+        assert!(! is_incomplete("let a = 1 + 2"));
+        assert!(! is_incomplete(""));
+        // Closing too much is an error, which the compiler reports.
+        assert!(! is_incomplete("f())"));
+    }
+
+    #[test]
+    fn brackets_in_strings_and_comments() {
+        assert!(! is_incomplete("println(\"{ ( [\")"));
+        assert!(! is_incomplete("let a = 1 // { ("));
+        assert!(! is_incomplete("let a = /* { */ 1"));
+        assert!(is_incomplete("fn f() { // }"));
+        assert!(is_incomplete("println(\")\""));
+    }
+
+    #[test]
+    fn completes_identifiers() {
+        assert_eq!(complete("$val"), (0, vec!["$value".to_owned(), "$values".to_owned()]));
+        assert_eq!(complete("1 + $values.$"), (12, vec!["$first".to_owned()]));
+        assert_eq!(complete("$values.s"), (8, vec!["second".to_owned()]));
+        assert_eq!(complete("valu"), (0, vec!["value".to_owned()]));
+        assert_eq!(complete("whi"), (0, vec!["while".to_owned()]));
+        assert_eq!(complete("import a from \"os/"), (15, vec!["os/env".to_owned(), "os/process".to_owned()]));
+    }
+}
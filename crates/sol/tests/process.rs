@@ -0,0 +1,87 @@
+//! Runs scripts that use `os/process` and checks what they print and the status they exit with.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+/// Run `sol run <script> <args>` in `dir` with `stdin` as its input.
+fn sol(dir: &PathBuf, script: &str, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sol"))
+        .current_dir(dir)
+        .arg("run")
+        .arg(script)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// A directory with `script.sol` in it, which is removed when the test is done with it.
+struct Script(PathBuf);
+
+impl Script {
+    fn new(name: &str, source: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("sol-process-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("script.sol"), source).unwrap();
+
+        Self(dir.canonicalize().unwrap())
+    }
+}
+
+impl Drop for Script {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn text(bytes: &[u8]) -> &str {
+    std::str::from_utf8(bytes).unwrap()
+}
+
+#[test]
+fn example() {
+    let example = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../examples/libraries/os/process.sol");
+    let script = Script::new("example", &std::fs::read_to_string(example).unwrap());
+
+    let output = sol(&script.0, "script.sol", &["one", "two words"], "first\nsecond\r\n");
+    let stdout = text(&output.stdout).lines().collect::<Vec<&str>>();
+
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(stdout[0], "Arguments: one, two words");
+    assert!(stdout[1].starts_with(&format!("Running in {} as process ", script.0.display())), "{}", stdout[1]);
+    assert_eq!(&stdout[2..], ["> first", "> second"]);
+    assert_eq!(text(&output.stderr), "Done!\n");
+}
+
+#[test]
+fn exits() {
+    let script = Script::new("exit", "import exit, stdin from \"os/process\"\n\nprint(stdin.readAll().length)\nexit()\nprintln(\"unreachable\")\n");
+    let output = sol(&script.0, "script.sol", &[], "abc\ndef\n");
+
+    // What was printed without a newline is flushed before exiting.
+    assert_eq!((output.status.code(), text(&output.stdout)), (Some(0), "8"));
+    assert_eq!(text(&output.stderr), "");
+}
+
+#[test]
+fn fails() {
+    let script = Script::new("fail", "import args from \"os/process\"\n\nprintln(args.length)\nmissing()\n");
+    let output = sol(&script.0, "script.sol", &[], "");
+
+    assert_eq!((output.status.code(), text(&output.stdout)), (Some(1), "0\n"));
+    assert_eq!(
+        text(&output.stderr),
+        format!("Uncaught ReferenceError: 'missing' is not defined\n    at <anonymous> ({}:4:1)\n", script.0.join("script.sol").display()),
+    );
+
+    let missing = sol(&script.0, "missing.sol", &[], "");
+
+    assert_eq!(missing.status.code(), Some(1));
+    assert!(text(&missing.stderr).starts_with("Could not read `missing.sol`: "), "{}", text(&missing.stderr));
+}
@@ -0,0 +1,156 @@
+//! Runs `examples/libraries/web/website.sol` on a loopback port and checks its answers over HTTP.
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// The example app, which is killed when the test is done with it.
+struct Server {
+    child: Child,
+    address: String,
+    dir: PathBuf,
+}
+
+impl Server {
+    fn start() -> Self {
+        // Ask the OS for a free port, then let the app listen on it.
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let address = format!("127.0.0.1:{}", port);
+
+        let example = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../examples/libraries/web/website.sol");
+        let source = std::fs::read_to_string(example).unwrap().replace("0.0.0.0:8080", &address);
+
+        let dir = std::env::temp_dir().join(format!("sol-website-{}-{}", std::process::id(), port));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("website.sol"), source).unwrap();
+
+        let child = Command::new(env!("CARGO_BIN_EXE_sol"))
+            .arg("run")
+            .arg(dir.join("website.sol"))
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let server = Self { child, address, dir };
+        let start = Instant::now();
+
+        while TcpStream::connect(&server.address).is_err() {
+            assert!(start.elapsed() < Duration::from_secs(10), "the app didn't start listening on {}", server.address);
+
+            std::thread::sleep(Duration::from_millis(50));
+        }
+
+        server
+    }
+
+    /// Send a request with `body` and read the whole response.
+    fn request(&self, method: &str, path: &str, headers: &[(&str, &str)], body: &str) -> Response {
+        let mut stream = TcpStream::connect(&self.address).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+
+        let mut request = format!("{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n", method, path, self.address, body.len());
+
+        for (name, value) in headers {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+
+        request.push_str("\r\n");
+        request.push_str(body);
+
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).unwrap();
+
+        Response::parse(&raw)
+    }
+
+    fn get(&self, path: &str) -> Response {
+        self.request("GET", path, &[], "")
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Response {
+    fn parse(raw: &str) -> Self {
+        let (head, body) = raw.split_once("\r\n\r\n").unwrap_or((raw, ""));
+        let mut lines = head.lines();
+
+        let status = lines.next().unwrap().split(' ').nth(1).unwrap().parse().unwrap();
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_owned()))
+            .collect();
+
+        Self { status, headers, body: body.to_owned() }
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(field, _)| field == name).map(|(_, value)| value.as_str())
+    }
+}
+
+#[test]
+fn website() {
+    let server = Server::start();
+
+    let home = server.get("/");
+    assert_eq!(home.status, 200);
+    assert_eq!(home.body, "This is the home page. Try visiting /hello to get a message.");
+    // Set by the middleware, on every route.
+    assert_eq!(home.header("x-powered-by"), Some("Sol"));
+
+    assert_eq!(server.get("/hello?name=Sol+Lang").body, "Hello, Sol Lang!");
+    assert_eq!(server.get("/hello").body, "Hello, world!");
+    assert_eq!(server.get("/hello/there").body, "Hello, there");
+
+    let redirect = server.get("/home");
+    assert_eq!(redirect.status, 302);
+    assert_eq!(redirect.header("location"), Some("/"));
+
+    let echo = server.request("POST", "/echo", &[("Content-Type", "application/json")], r#"{"a":[1,2]}"#);
+    assert_eq!(echo.status, 201);
+    assert_eq!(echo.header("content-type"), Some("application/json"));
+    assert_eq!(echo.body, r#"{"a":[1,2]}"#);
+
+    let invalid = server.request("POST", "/echo", &[], "{");
+    assert_eq!((invalid.status, invalid.body.as_str()), (400, "400 Bad Request"));
+
+    let login = server.request("POST", "/login", &[("Content-Type", "application/x-www-form-urlencoded")], "user=sol+lang%21&remember=1");
+    assert_eq!(login.status, 200);
+    assert_eq!(login.body, r#"{"user":"sol lang!"}"#);
+
+    // Only the first `=` ends the key.
+    let equals = server.request("POST", "/login", &[("Content-Type", "application/x-www-form-urlencoded")], "user=a=b%3D&token");
+    assert_eq!(equals.body, r#"{"user":"a=b="}"#);
+
+    let escape = server.request("POST", "/login", &[("Content-Type", "application/x-www-form-urlencoded")], "user=%zz");
+    assert_eq!((escape.status, escape.body.as_str()), (400, "400 Bad Request"));
+
+    let missing = server.get("/missing");
+    assert_eq!((missing.status, missing.body.as_str()), (404, "404 Not Found"));
+
+    let method = server.request("DELETE", "/hello", &[], "");
+    assert_eq!((method.status, method.body.as_str()), (405, "405 Method Not Allowed"));
+    assert_eq!(method.header("allow"), Some("GET"));
+
+    let post = server.request("PUT", "/echo", &[], "");
+    assert_eq!(post.status, 405);
+    assert_eq!(post.header("allow"), Some("POST"));
+}
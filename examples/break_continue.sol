@@ -0,0 +1,15 @@
+let i = 0
+
+while true {
+    i = i + 1
+
+    if i % 2 == 0 {
+        continue
+    }
+
+    if i > 9 {
+        break
+    }
+
+    println(i)
+}
@@ -0,0 +1,29 @@
+import read, write, append, copy, remove, create_dir, read_dir, exists, glob, temp_dir, lines from "fs"
+
+let dir = temp_dir()
+
+create_dir(dir + "/notes/old")
+write(dir + "/notes/todo.txt", "Write docs\n")
+append(dir + "/notes/todo.txt", "Fix bugs\n")
+copy(dir + "/notes/todo.txt", dir + "/notes/old/todo.txt")
+
+println(lines(dir + "/notes/todo.txt"))
+
+let entries = read_dir(dir + "/notes")
+let i = 0
+
+while i < entries.length {
+    let entry = entries[i]
+
+    println(entry.name, entry.is_dir, entry.size)
+    i = i + 1
+}
+
+println(glob(dir + "/**/*.txt").length, "text files")
+
+remove(dir)
+
+println(exists(dir))
+
+// Errors are thrown as JavaScript errors, with the kind of the error in `kind`.
+read(dir + "/missing.txt")
@@ -0,0 +1,14 @@
+import args, exit, stdin, stderr, pid, cwd from "os/process"
+
+println("Arguments: " + args.join(", "))
+println("Running in " + cwd + " as process " + pid)
+
+let line = stdin.readLine()
+
+while line != null {
+    println("> " + line)
+    line = stdin.readLine()
+}
+
+stderr.write("Done!\n")
+exit(3)
@@ -0,0 +1,9 @@
+import join, normalize, dirname, basename, extension, stem, is_absolute, relative, canonicalize from "path"
+
+let file = join(__DIR__, "..", "path", "path.sol")
+
+println(normalize(file) == canonicalize(file))
+println(normalize("a/./b/../c"))
+println(dirname("src/main.sol"), basename("src/main.sol"), stem("src/main.sol"), extension("src/main.sol"))
+println(is_absolute("/usr/bin"), is_absolute("src"))
+println(relative("src/web", "src/fs/read.sol"))
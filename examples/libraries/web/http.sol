@@ -2,8 +2,16 @@ import Server from "web/http"
 
 Server
     .init("0.0.0.0:8099")
-    .serve(fn (method, url) {
-        println("Handling request for " + method + " at " + url + "...")
+    .serve(fn (request) {
+        println("Handling request for " + request.method + " at " + request.url + "...")
 
-        return "Hello, world!"
-    })
\ No newline at end of file
+        if request.path == "/json" {
+            return {
+                "status": 200,
+                "headers": { "Content-Type": "application/json" },
+                "body": "{ \"hello\": \"world\" }",
+            }
+        }
+
+        return "Hello, " + (request.query.name || "world") + "!"
+    })
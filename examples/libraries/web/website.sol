@@ -2,13 +2,27 @@ import App from "web/website"
 
 App
     .init("0.0.0.0:8080")
+    .use(fn (request, response, next) {
+        response.header("X-Powered-By", "Sol")
+
+        return next()
+    })
     .get("/", fn (request) {
         return "This is the home page. Try visiting /hello to get a message."
     })
     .get("/hello", fn (request) {
-        return "Hello, world!"
+        return "Hello, " + (request.query.name || "world") + "!"
     })
     .get("/hello/:name", fn (request) {
         return "Hello, " + request.get("name")
     })
-    .run()
\ No newline at end of file
+    .get("/home", fn (request, response) -> response.redirect("/"))
+    .post("/echo", fn (request, response) {
+        return response.status(201).json(request.json())
+    })
+    .post("/login", fn (request, response) {
+        let form = request.form()
+
+        return response.json({ "user": form.user })
+    })
+    .run()
@@ -0,0 +1,10 @@
+import square, circle, shapes, unit from "./shapes"
+import { square as area } from "./shapes"
+import * as geometry from "./shapes"
+
+println(shapes)
+println(square(4))
+println(circle(unit))
+
+// The same function, under another name and as a member of the whole module.
+println(area(2) == geometry.square(2))
@@ -0,0 +1,15 @@
+// Everything that `main.sol` imports is exported here.
+
+export let unit = 1
+
+export fn square(side) {
+    return side * side
+}
+
+fn circle(radius) {
+    return 3.14159 * radius * radius
+}
+
+let shapes = ["square", "circle"]
+
+export { circle, shapes }
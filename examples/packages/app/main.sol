@@ -0,0 +1,6 @@
+// Installed packages are imported by their name, which imports their entry point, or by the path of a file in them.
+import greet from "greeting"
+import shout from "greeting/loud"
+
+println(greet("World"))
+println(shout("World"))
@@ -0,0 +1,5 @@
+import greet from "./main"
+
+export fn shout(name) {
+    return greet(name).toUpperCase()
+}
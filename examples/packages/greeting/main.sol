@@ -0,0 +1,3 @@
+export fn greet(name) {
+    return "Hello, " + name + "!"
+}
@@ -0,0 +1,11 @@
+let language = { "name": "Sol", "tags": ["small", "fun"], "parent": null }
+
+println(language)
+println([1, [2, [3]]], "top-level strings aren't quoted")
+
+print("Loading...")
+println(" done")
+
+eprintln("Printed to stderr")
+
+println(format("{} has {} tags", language.name, language.tags.length))